@@ -3,13 +3,14 @@
 extern crate test;
 
 use rstrain::model::Model;
+use rstrain::search::SearchConfig;
 use rstrain::tabu::TabuGeneticSearch;
 use test::Bencher;
 
 #[bench]
 fn search(b: &mut Bencher) {
     let model = Model::new_for_bench();
-    let mut tabu = TabuGeneticSearch::new(0, 1000, false);
+    let mut tabu = TabuGeneticSearch::new(SearchConfig::default().time(0).iterations(1000));
 
     b.iter(|| tabu.search(&model));
 }
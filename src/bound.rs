@@ -0,0 +1,286 @@
+use crate::flow::MinCostFlow;
+use crate::model::Model;
+use crate::passenger::Id as PId;
+use crate::station::Id as SId;
+use crate::train::StartStation;
+use crate::types::{Time, TimeDiff};
+
+/// Gets the earliest possible arrival time of the given passenger.
+///
+/// The arrival time is determined by the fastest train that fits the group
+/// size and could reach the start station of the passenger, all other
+/// passengers and capacities are neglected. `None` is returned when no train
+/// fits the group size.
+pub fn earliest_arrival(model: &Model, p_id: PId) -> Option<Time> {
+    let passenger = &model.passengers[p_id];
+
+    model
+        .trains
+        .iter()
+        .enumerate()
+        .filter(|(_, train)| train.capacity >= passenger.size)
+        .map(|(t_id, train)| {
+            // passengers can be boarded at t = 1 at the earliest
+            let board = match train.start {
+                StartStation::Station(s_id) => model
+                    .travel_time(t_id, s_id, passenger.start)
+                    .saturating_add(1),
+                StartStation::Any => 1,
+            };
+
+            // the train departs one tick after boarding, the passenger is
+            // detrained as soon as the train arrives
+            model
+                .travel_time(t_id, passenger.start, passenger.destination)
+                .saturating_add(board.saturating_add(1))
+        })
+        .min()
+}
+
+/// Gets the minimum number of ticks from boarding the given passenger until
+/// the passenger arrives at the destination, `None` is returned when no train
+/// fits the group size.
+pub fn min_journey_time(model: &Model, p_id: PId) -> Option<Time> {
+    let passenger = &model.passengers[p_id];
+
+    model
+        .trains
+        .iter()
+        .enumerate()
+        .filter(|(_, train)| train.capacity >= passenger.size)
+        .map(|(t_id, _)| {
+            model
+                .travel_time(t_id, passenger.start, passenger.destination)
+                .saturating_add(1)
+        })
+        .min()
+}
+
+/// Gets a lower bound of the delay for each passenger.
+///
+/// Passengers that cannot arrive until `t_max` are bound by `t_max`, which is
+/// the delay that is assigned to passengers that have not arrived.
+pub fn passenger_bounds(model: &Model) -> Vec<TimeDiff> {
+    (0..model.passengers.len())
+        .map(|p_id| match earliest_arrival(model, p_id) {
+            Some(arrival) if arrival <= model.t_max => {
+                (arrival as TimeDiff - model.passengers[p_id].arrival as TimeDiff).max(0)
+            }
+            _ => model.t_max as TimeDiff,
+        })
+        .collect()
+}
+
+/// Gets a lower bound of the total delay of the model.
+pub fn delay_lower_bound(model: &Model) -> TimeDiff {
+    passenger_bounds(model).iter().sum()
+}
+
+/// The maximum number of passengers of a station whose boardings are
+/// relaxed as min-cost flow, larger stations are bound per passenger as the
+/// flow network grows quadratically.
+const MAX_FLOW_PASSENGERS: usize = 300;
+
+/// Gets a lower bound of the total delay by a network-flow relaxation on the
+/// time-expanded graph of the start stations.
+///
+/// Each passenger is boarded at its start station at some point in time,
+/// which is limited by the station capacity, as boarding trains are located
+/// at the station, and by the number of trains. Boarding at `b` results in
+/// the delay of arriving at `b` plus the minimum journey time. All other
+/// constraints are neglected, in particular connection capacities, which
+/// limit trains rather than passengers, as a train carries several groups.
+/// The bound is at least the sum of the passenger bounds.
+pub fn flow_lower_bound(model: &Model) -> TimeDiff {
+    let bounds = passenger_bounds(model);
+    let mut by_station: Vec<Vec<PId>> = model.stations.iter().map(|_| vec![]).collect();
+
+    for (p_id, passenger) in model.passengers.iter().enumerate() {
+        by_station[passenger.start].push(p_id);
+    }
+
+    by_station
+        .iter()
+        .enumerate()
+        .map(|(s_id, passengers)| {
+            let sum: TimeDiff = passengers.iter().map(|&p_id| bounds[p_id]).sum();
+
+            if passengers.len() > MAX_FLOW_PASSENGERS {
+                return sum;
+            }
+
+            sum.max(boarding_bound(model, s_id, passengers, &bounds))
+        })
+        .sum()
+}
+
+/// Gets the minimum total delay of the passengers of a station, when at most
+/// as many passengers as trains fit into the station are boarded per point in
+/// time.
+fn boarding_bound(model: &Model, s_id: SId, passengers: &[PId], bounds: &[TimeDiff]) -> TimeDiff {
+    let t_max = model.t_max as TimeDiff;
+    let slots = (model.stations[s_id].capacity.max(0) as usize).min(model.trains.len());
+
+    // the earliest boarding and the journey time of each passenger, every
+    // passenger can be boarded within as many points in time as there are
+    // passengers at the station after its earliest boarding
+    let windows: Vec<Option<(Time, Time)>> = passengers
+        .iter()
+        .map(|&p_id| {
+            let board = earliest_board(model, p_id)?;
+            let journey = min_journey_time(model, p_id)?;
+
+            Some((board, journey))
+        })
+        .collect();
+
+    let first = windows
+        .iter()
+        .flatten()
+        .map(|&(board, _)| board)
+        .min()
+        .unwrap_or(1);
+    let last = windows
+        .iter()
+        .flatten()
+        .map(|&(board, _)| board + passengers.len())
+        .max()
+        .unwrap_or(first)
+        .min(model.t_max);
+
+    if slots == 0 || last < first {
+        return passengers.iter().map(|&p_id| bounds[p_id]).sum();
+    }
+
+    // nodes: source, sink, passengers, boarding points in time
+    let source = 0;
+    let sink = 1;
+    let slot = |t: Time| 2 + passengers.len() + t - first;
+    let mut network = MinCostFlow::new(2 + passengers.len() + last - first + 1);
+
+    for (i, (&p_id, window)) in passengers.iter().zip(windows.iter()).enumerate() {
+        network.add_edge(source, 2 + i, 1, 0);
+
+        // not arriving is always possible
+        network.add_edge(2 + i, sink, 1, t_max as i64);
+
+        if let Some((board, journey)) = *window {
+            let deadline = model.passengers[p_id].arrival as TimeDiff;
+
+            for b in board..=(board + passengers.len()).min(last) {
+                let arrival = b.saturating_add(journey);
+
+                if arrival > model.t_max {
+                    break;
+                }
+
+                let delay = (arrival as TimeDiff - deadline).max(bounds[p_id]);
+
+                network.add_edge(2 + i, slot(b), 1, delay as i64);
+            }
+        }
+    }
+
+    for t in first..=last {
+        network.add_edge(slot(t), sink, slots as i64, 0);
+    }
+
+    network.run(source, sink, passengers.len() as i64).1 as TimeDiff
+}
+
+/// Gets the earliest point in time the given passenger can be boarded by any
+/// train that fits the group size.
+fn earliest_board(model: &Model, p_id: PId) -> Option<Time> {
+    let passenger = &model.passengers[p_id];
+
+    model
+        .trains
+        .iter()
+        .enumerate()
+        .filter(|(_, train)| train.capacity >= passenger.size)
+        .map(|(t_id, train)| match train.start {
+            StartStation::Station(s_id) => model
+                .travel_time(t_id, s_id, passenger.start)
+                .saturating_add(1),
+            StartStation::Any => 1,
+        })
+        .min()
+}
+
+#[test]
+fn it_bounds_the_delay_by_the_fastest_fitting_train() {
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 2
+S2 2
+S3 2
+[Lines]
+L1 S1 S2 4 1
+L2 S2 S3 4 1
+[Trains]
+T1 S1 2 5
+T2 S3 4 10
+[Passengers]
+P1 S1 S3 5 3
+P2 S3 S1 10 4
+"
+        .to_owned(),
+    );
+
+    // P1: T1 boards at 1, departs at 2 and needs 4 ticks to S3
+    assert_eq!(earliest_arrival(&model, 0), Some(6));
+    // P2: only T2 fits, it boards at 1, departs at 2 and needs 2 ticks to S1
+    assert_eq!(earliest_arrival(&model, 1), Some(4));
+    assert_eq!(passenger_bounds(&model), vec![3, 0]);
+    assert_eq!(delay_lower_bound(&model), 3);
+}
+
+#[test]
+fn it_bounds_the_delay_by_the_boardings_per_station() {
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 1
+S2 2
+[Lines]
+L1 S1 S2 2 1
+[Trains]
+T1 * 1 5
+T2 * 1 5
+[Passengers]
+P1 S1 S2 5 4
+P2 S1 S2 5 4
+P3 S1 S2 5 4
+"
+        .to_owned(),
+    );
+
+    // each passenger arrives at 4 at the earliest, but only one passenger can
+    // be boarded per point in time at S1
+    assert_eq!(delay_lower_bound(&model), 0);
+    assert_eq!(flow_lower_bound(&model), 3);
+}
+
+#[test]
+fn it_bounds_passengers_out_of_reach_by_t_max() {
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 2
+S2 2
+S3 2
+[Lines]
+L1 S1 S2 4 1
+[Trains]
+T1 S3 2 5
+[Passengers]
+P1 S1 S2 5 3
+"
+        .to_owned(),
+    );
+
+    // T1 cannot reach S1, the passenger is bound by t_max
+    assert_eq!(earliest_arrival(&model, 0), Some(Time::MAX));
+    assert_eq!(passenger_bounds(&model), vec![model.t_max as TimeDiff]);
+}
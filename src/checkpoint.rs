@@ -0,0 +1,407 @@
+use crate::model::Model;
+use crate::move_::{Board, Depart, Detrain, Move, Start};
+use crate::types::{Time, TimeDiff};
+use fxhash::hash64;
+use std::fs;
+use std::str::FromStr;
+
+/// A snapshot of a running search, that can be written to a file to resume
+/// the search later on.
+///
+/// Solutions are stored as the moves of each point in time, the states are
+/// restored by replaying the moves with the same model.
+pub struct Checkpoint {
+    /// The hash of the model the search has been running on.
+    pub model_hash: u64,
+
+    /// The maximum point in time of the model, which may have been raised
+    /// during the search.
+    pub t_max: Time,
+
+    /// The seed of the random generator.
+    pub seed: [u8; 32],
+
+    /// The stream of the random generator.
+    pub stream: u64,
+
+    /// The word position of the random generator.
+    pub word_pos: u128,
+
+    /// The hashes of the tabu list in insertion order, which is empty for a
+    /// Bloom filter.
+    pub tabu: Vec<u64>,
+
+    /// The best sum of delays of all tracked iterations.
+    pub fitness: Vec<TimeDiff>,
+
+    /// The number of moves that have been checked.
+    pub checked_moves: usize,
+
+    /// The number of iterations.
+    pub iterations: usize,
+
+    /// The number of restarts.
+    pub restarts: usize,
+
+    /// The number of trains that are moved by the search.
+    pub trains: usize,
+
+    /// The number of iterations without improvement.
+    pub no_improvements: u128,
+
+    /// The minimal delay of all iterations.
+    pub min_delay: TimeDiff,
+
+    /// The number of milli seconds the search has been running.
+    pub elapsed: u128,
+
+    /// The moves of the best solution.
+    pub best: Vec<Vec<Move>>,
+
+    /// The moves of the current solution prefix, which is continued when the
+    /// search is resumed.
+    pub current: Vec<Vec<Move>>,
+
+    /// The state of the adaptation of the used trains.
+    pub adaptation: Adaptation,
+}
+
+/// The state of the adaptation of the number of used trains, see
+/// [adapt_trains](crate::search::SearchConfig::adapt_trains).
+#[derive(Default)]
+pub struct Adaptation {
+    /// Whether the number of used trains is still adapted.
+    pub adapting: bool,
+
+    /// The number of restarts of the current phase.
+    pub phase_restarts: usize,
+
+    /// The number of illegal solutions of the current phase.
+    pub phase_illegal: usize,
+
+    /// Whether the current phase has improved its best solution.
+    pub phase_improved: bool,
+
+    /// The best delay that has been reached for each number of used trains.
+    pub best_by_trains: Vec<TimeDiff>,
+
+    /// The moves of the best solution of the current phase.
+    pub phase_best: Vec<Vec<Move>>,
+}
+
+impl Checkpoint {
+    /// Gets the hash of the given model, that is used to verify that a
+    /// checkpoint is resumed with the same model. Only the parsed instance is
+    /// hashed, `t_max` is stored in the checkpoint.
+    pub fn model_hash(model: &Model) -> u64 {
+        hash64(model)
+    }
+
+    /// Writes the checkpoint to the given path.
+    ///
+    /// The checkpoint is written to a temporary file first, so that an
+    /// existing checkpoint is not corrupted when the process is killed.
+    pub fn write(&self, path: &str) -> std::io::Result<()> {
+        let mut string = "[Checkpoint]\n".to_owned();
+
+        string.push_str(&format!("model {:x}\n", self.model_hash));
+        string.push_str(&format!("t_max {}\n", self.t_max));
+        string.push_str(&format!(
+            "rng {} {} {}\n",
+            self.seed
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>(),
+            self.stream,
+            self.word_pos
+        ));
+        string.push_str(&format!("checked_moves {}\n", self.checked_moves));
+        string.push_str(&format!("iterations {}\n", self.iterations));
+        string.push_str(&format!("restarts {}\n", self.restarts));
+        string.push_str(&format!("trains {}\n", self.trains));
+        string.push_str(&format!("no_improvements {}\n", self.no_improvements));
+        string.push_str(&format!("min_delay {}\n", self.min_delay));
+        string.push_str(&format!("elapsed {}\n", self.elapsed));
+        string.push_str(&format!("adapting {}\n", self.adaptation.adapting));
+        string.push_str(&format!(
+            "phase_restarts {}\n",
+            self.adaptation.phase_restarts
+        ));
+        string.push_str(&format!(
+            "phase_illegal {}\n",
+            self.adaptation.phase_illegal
+        ));
+        string.push_str(&format!(
+            "phase_improved {}\n",
+            self.adaptation.phase_improved
+        ));
+        string.push_str(&format!(
+            "best_by_trains {}\n",
+            self.adaptation
+                .best_by_trains
+                .iter()
+                .map(|delay| delay.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        ));
+
+        string.push_str("[Tabu]\n");
+        for hash in &self.tabu {
+            string.push_str(&format!("{}\n", hash));
+        }
+
+        string.push_str("[Fitness]\n");
+        for fitness in &self.fitness {
+            string.push_str(&format!("{}\n", fitness));
+        }
+
+        string.push_str("[Best]\n");
+        write_moves(&mut string, &self.best);
+
+        string.push_str("[Current]\n");
+        write_moves(&mut string, &self.current);
+
+        string.push_str("[PhaseBest]\n");
+        write_moves(&mut string, &self.adaptation.phase_best);
+
+        let tmp = format!("{}.tmp", path);
+        fs::write(&tmp, string)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Reads a checkpoint from the given path and verifies that it has been
+    /// written for the given model.
+    pub fn read(path: &str, model: &Model) -> Result<Checkpoint, String> {
+        let string = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read checkpoint \"{}\": {}", path, e))?;
+
+        let mut checkpoint = Checkpoint {
+            model_hash: 0,
+            t_max: model.t_max,
+            seed: [0; 32],
+            stream: 0,
+            word_pos: 0,
+            tabu: vec![],
+            fitness: vec![],
+            checked_moves: 0,
+            iterations: 0,
+            restarts: 0,
+            trains: model.used_trains,
+            no_improvements: 0,
+            min_delay: TimeDiff::MAX,
+            elapsed: 0,
+            best: vec![],
+            current: vec![],
+            adaptation: Adaptation::default(),
+        };
+        let mut section = "";
+
+        for line in string.lines() {
+            if line.starts_with('[') {
+                section = line;
+                continue;
+            }
+
+            let invalid = || format!("Invalid checkpoint line \"{}\"!", line);
+            let attributes: Vec<&str> = line.split_whitespace().collect();
+
+            match section {
+                "[Checkpoint]" => match attributes.as_slice() {
+                    ["model", hash] => {
+                        checkpoint.model_hash =
+                            u64::from_str_radix(hash, 16).map_err(|_| invalid())?
+                    }
+                    ["rng", seed, stream, word_pos] if seed.len() == 64 => {
+                        for (i, byte) in checkpoint.seed.iter_mut().enumerate() {
+                            *byte = u8::from_str_radix(&seed[i * 2..i * 2 + 2], 16)
+                                .map_err(|_| invalid())?;
+                        }
+                        checkpoint.stream = parse(stream, line)?;
+                        checkpoint.word_pos = parse(word_pos, line)?;
+                    }
+                    ["t_max", value] => checkpoint.t_max = parse(value, line)?,
+                    ["checked_moves", value] => checkpoint.checked_moves = parse(value, line)?,
+                    ["iterations", value] => checkpoint.iterations = parse(value, line)?,
+                    ["restarts", value] => checkpoint.restarts = parse(value, line)?,
+                    ["trains", value] => checkpoint.trains = parse(value, line)?,
+                    ["no_improvements", value] => checkpoint.no_improvements = parse(value, line)?,
+                    ["min_delay", value] => checkpoint.min_delay = parse(value, line)?,
+                    ["elapsed", value] => checkpoint.elapsed = parse(value, line)?,
+                    ["adapting", value] => checkpoint.adaptation.adapting = parse(value, line)?,
+                    ["phase_restarts", value] => {
+                        checkpoint.adaptation.phase_restarts = parse(value, line)?
+                    }
+                    ["phase_illegal", value] => {
+                        checkpoint.adaptation.phase_illegal = parse(value, line)?
+                    }
+                    ["phase_improved", value] => {
+                        checkpoint.adaptation.phase_improved = parse(value, line)?
+                    }
+                    ["best_by_trains", values @ ..] => {
+                        checkpoint.adaptation.best_by_trains = values
+                            .iter()
+                            .map(|value| parse(value, line))
+                            .collect::<Result<_, _>>()?
+                    }
+                    _ => return Err(invalid()),
+                },
+                "[Tabu]" => checkpoint.tabu.push(parse(line, line)?),
+                "[Fitness]" => checkpoint.fitness.push(parse(line, line)?),
+                "[Best]" => checkpoint.best.push(read_moves(&attributes, line)?),
+                "[Current]" => checkpoint.current.push(read_moves(&attributes, line)?),
+                "[PhaseBest]" => checkpoint
+                    .adaptation
+                    .phase_best
+                    .push(read_moves(&attributes, line)?),
+                _ => return Err(invalid()),
+            }
+        }
+
+        if checkpoint.model_hash != Checkpoint::model_hash(model) {
+            return Err(format!(
+                "The checkpoint \"{}\" has been written for a different model!",
+                path
+            ));
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+fn parse<T: FromStr>(value: &str, line: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid checkpoint line \"{}\"!", line))
+}
+
+/// Writes one line per point in time, the line starts with the time followed
+/// by the encoded moves.
+fn write_moves(string: &mut String, moves: &[Vec<Move>]) {
+    for (t, tick) in moves.iter().enumerate() {
+        string.push_str(&t.to_string());
+
+        for m in tick {
+            string.push(' ');
+            string.push_str(&match m {
+                Move::Board(m) => format!("board:{}:{}:{}", m.t_id, m.p_id, m.s_id),
+                Move::Detrain(m) => format!("detrain:{}:{}:{}", m.t_id, m.p_id, m.s_id),
+                Move::Depart(m) => format!("depart:{}:{}:{}:{}", m.t_id, m.from, m.to, m.c_id),
+                Move::Start(m) => format!("start:{}:{}", m.t_id, m.s_id),
+                Move::None(_) => "none".to_string(),
+            });
+        }
+
+        string.push('\n');
+    }
+}
+
+fn read_moves(attributes: &[&str], line: &str) -> Result<Vec<Move>, String> {
+    attributes
+        .iter()
+        .skip(1)
+        .map(|attribute| {
+            let parts: Vec<&str> = attribute.split(':').collect();
+            let ids = parts[1..]
+                .iter()
+                .map(|id| parse(id, line))
+                .collect::<Result<Vec<usize>, String>>()?;
+
+            match (parts[0], ids.as_slice()) {
+                ("board", &[t_id, p_id, s_id]) => Ok(Move::Board(Board { t_id, p_id, s_id })),
+                ("detrain", &[t_id, p_id, s_id]) => Ok(Move::Detrain(Detrain { t_id, p_id, s_id })),
+                ("depart", &[t_id, from, to, c_id]) => Ok(Move::Depart(Depart {
+                    t_id,
+                    from,
+                    to,
+                    c_id,
+                })),
+                ("start", &[t_id, s_id]) => Ok(Move::Start(Start { t_id, s_id })),
+                ("none", &[]) => Ok(Move::None(crate::move_::None())),
+                _ => Err(format!("Invalid checkpoint line \"{}\"!", line)),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn it_reads_written_checkpoints() {
+    let mut model =
+        crate::parser::parse(&include_str!("../test-cases/simple/input.txt").to_owned());
+    let timetable =
+        crate::parser::parse_timetable(include_str!("../test-cases/simple/output.txt"), &model)
+            .unwrap();
+    let best = crate::solution::Solution::from_timetable(&model, &timetable).moves();
+    let path = std::env::temp_dir().join(format!("rstrain-{}.checkpoint", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    model.t_max += 10;
+
+    let checkpoint = Checkpoint {
+        model_hash: Checkpoint::model_hash(&model),
+        t_max: model.t_max,
+        seed: [7; 32],
+        stream: 3,
+        word_pos: 42,
+        tabu: vec![1, 2, 3],
+        fitness: vec![18, 9, 3],
+        checked_moves: 100,
+        iterations: 10,
+        restarts: 1,
+        trains: 2,
+        no_improvements: 4,
+        min_delay: 3,
+        elapsed: 500,
+        current: best[..3].to_vec(),
+        adaptation: Adaptation {
+            adapting: true,
+            phase_restarts: 5,
+            phase_illegal: 2,
+            phase_improved: true,
+            best_by_trains: vec![TimeDiff::MAX, 9, 3],
+            phase_best: best[..5].to_vec(),
+        },
+        best,
+    };
+
+    checkpoint.write(path).unwrap();
+
+    // the raised t_max does not change the hash of the model
+    model.t_max -= 10;
+
+    let read = Checkpoint::read(path, &model).unwrap();
+
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(read.t_max, checkpoint.t_max);
+    assert_eq!(read.seed, checkpoint.seed);
+    assert_eq!(
+        (read.stream, read.word_pos, read.tabu, read.fitness),
+        (3, 42, vec![1, 2, 3], vec![18, 9, 3])
+    );
+    assert_eq!(
+        (
+            read.checked_moves,
+            read.iterations,
+            read.restarts,
+            read.trains
+        ),
+        (100, 10, 1, 2)
+    );
+    assert_eq!(
+        (read.no_improvements, read.min_delay, read.elapsed),
+        (4, 3, 500)
+    );
+    assert!(read.best == checkpoint.best);
+    assert!(read.current == checkpoint.current);
+    assert_eq!(
+        (
+            read.adaptation.adapting,
+            read.adaptation.phase_restarts,
+            read.adaptation.phase_illegal,
+            read.adaptation.phase_improved,
+            read.adaptation.best_by_trains
+        ),
+        (true, 5, 2, true, vec![TimeDiff::MAX, 9, 3])
+    );
+    assert!(read.adaptation.phase_best == checkpoint.adaptation.phase_best);
+}
@@ -0,0 +1,187 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::search::Objective;
+use crate::solution::Solution;
+use crate::types::Time;
+
+/// Moves of a solution that are removed together, each move is given with
+/// its point in time.
+type Removal = Vec<(Time, Move)>;
+
+/// Removes wasted moves from the solution: trains that never board a
+/// passenger, departures that return to the same station without boarding
+/// or detraining a passenger, departures after the last passenger has been
+/// detrained and detrains that are boarded again by the same train.
+///
+/// Each removal is verified by replaying the solution, it is kept when all
+/// moves are still legal and the fitness has not become worse. The cleaned
+/// solution and the number of removed moves are returned.
+pub fn clean_up(model: &Model, solution: &Solution, objective: Objective) -> (Solution, usize) {
+    let fitness = objective.fitness(solution);
+    let mut solution = solution.clone();
+    let mut removed = 0;
+
+    loop {
+        let mut changed = false;
+
+        for removal in removals(&solution.moves(), model) {
+            if let Some(cleaned) = without(&solution, &removal, model) {
+                if objective.fitness(&cleaned) <= fitness {
+                    removed += removal.len();
+                    solution = cleaned;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    (solution, removed)
+}
+
+/// Gets the candidate removals of the given moves.
+fn removals(moves: &[Vec<Move>], model: &Model) -> Vec<Removal> {
+    let mut removals = vec![];
+
+    for t_id in 0..model.trains.len() {
+        let train: Vec<(Time, Move)> = moves
+            .iter()
+            .enumerate()
+            .flat_map(|(t, tick)| tick.iter().map(move |&m| (t, m)))
+            .filter(|(_, m)| m.t_id() == Some(t_id))
+            .collect();
+
+        // trains that never board a passenger
+        if !train.is_empty() && !train.iter().any(|(_, m)| matches!(m, Move::Board(_))) {
+            removals.push(train.clone());
+            continue;
+        }
+
+        // detrains that are boarded again by the same train before it departs
+        for (i, (_, m)) in train.iter().enumerate() {
+            if let Move::Detrain(detrain) = m {
+                let board = train[i + 1..]
+                    .iter()
+                    .take_while(|(_, next)| !matches!(next, Move::Depart(_)))
+                    .find(|(_, next)| {
+                        matches!(next, Move::Board(board) if board.p_id == detrain.p_id)
+                    });
+
+                if let Some(&board) = board {
+                    removals.push(vec![train[i], board]);
+                }
+            }
+        }
+
+        // departures that return to the same station
+        for (i, (_, m)) in train.iter().enumerate() {
+            if let Move::Depart(first) = m {
+                for (j, (_, next)) in train.iter().enumerate().skip(i) {
+                    match next {
+                        Move::Depart(last) if last.to == first.from => {
+                            removals.push(train[i..=j].to_vec());
+                            break;
+                        }
+                        Move::Depart(_) => (),
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        // departures after the last boarding or detraining
+        let trailing: Removal = train
+            .iter()
+            .rev()
+            .take_while(|(_, m)| matches!(m, Move::Depart(_)))
+            .copied()
+            .collect();
+
+        if !trailing.is_empty() {
+            removals.push(trailing);
+        }
+    }
+
+    removals
+}
+
+/// Replays the solution without the given moves from the earliest point in
+/// time of the removal, `None` is returned when a move is missing or not
+/// legal anymore.
+fn without(solution: &Solution, removal: &Removal, model: &Model) -> Option<Solution> {
+    let mut moves = solution.moves();
+
+    for (t, m) in removal {
+        let i = moves.get(*t)?.iter().position(|other| other == m)?;
+
+        moves[*t].remove(i);
+    }
+
+    let from = removal.iter().map(|&(t, _)| t).min()?;
+    let mut state = match from {
+        0 => model.initial_state(),
+        _ => solution.0[from - 1].clone(),
+    };
+    let mut cleaned = Solution(solution.0[..from].to_vec());
+
+    for (t, tick) in moves.iter().enumerate().skip(from) {
+        if t > 0 {
+            state.next(model);
+
+            if !state.is_legal() {
+                return None;
+            }
+        }
+
+        for m in tick {
+            if !state.is_legal_move(m, model) {
+                return None;
+            }
+
+            state.push(*m, model);
+        }
+
+        cleaned.0.push(state.clone());
+    }
+
+    Some(cleaned)
+}
+
+#[test]
+fn it_removes_unused_trains() {
+    use crate::solution::Entry;
+
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 2
+S2 2
+[Lines]
+L1 S1 S2 2 1
+[Trains]
+T1 S1 1 5
+T2 * 1 5
+[Passengers]
+P1 S1 S2 5 10
+"
+        .to_owned(),
+    );
+
+    // T2 starts but never carries a passenger
+    let timetable = vec![
+        vec![Entry::Start(1, 1)],
+        vec![Entry::Board(0, 0)],
+        vec![Entry::Depart(0, 0)],
+        vec![],
+        vec![Entry::Detrain(0)],
+    ];
+    let solution = Solution::from_timetable(&model, &timetable);
+    let (cleaned, removed) = clean_up(&model, &solution, Objective::TotalDelay);
+
+    assert_eq!(removed, 1);
+    assert_eq!(cleaned.fitness(), solution.fitness());
+    assert_eq!(cleaned.used_trains(), 1);
+}
@@ -8,7 +8,7 @@ pub type Distance = Decimal;
 pub type Connections = Vec<Connection>;
 
 /// A connection between station a and station b.
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 pub struct Connection {
     /// The name of the connection.
     pub name: Name,
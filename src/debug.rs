@@ -1,13 +1,52 @@
+use crate::bound::{delay_lower_bound, flow_lower_bound};
 use crate::model::Model;
-use crate::solution::Solution;
+use crate::rule::Statistics as RuleStatistics;
+use crate::search::{Objective, SearchReport};
 use prettytable::{Cell, Row, Table};
 
 /// Prints detailed information about a search.
-pub fn debug(model: Model, solution: Solution, duration: u128, checked_moves: usize) {
+pub fn debug(model: Model, report: &SearchReport, objective: Objective) {
+    let (solution, duration, checked_moves) =
+        (&report.solution, report.duration, report.checked_moves);
     let mut table = Table::new();
+    let fitness = objective.fitness(solution);
+    let gap = fitness - objective.lower_bound(&model);
 
     println!("\n{}", solution.to_string(&model, true));
 
+    // the journeys of the passengers that transfer between trains
+    for (p_id, legs) in solution.journeys(&model).iter().enumerate() {
+        if legs.len() < 2 {
+            continue;
+        }
+
+        let legs: Vec<String> = legs
+            .iter()
+            .map(|leg| {
+                format!(
+                    "{} {}@{} -> {}@{}",
+                    model.trains[leg.t_id].name,
+                    model.stations[leg.from].name,
+                    leg.board,
+                    model.stations[leg.to].name,
+                    leg.detrain
+                )
+            })
+            .collect();
+
+        println!("{}: {}", model.passengers[p_id].name, legs.join(", "));
+    }
+
+    // the trains that still block each other in the best solution
+    for (from, to, group) in solution.deadlocks(&model) {
+        let trains: Vec<&str> = group
+            .iter()
+            .map(|&t_id| model.trains[t_id].name.as_str())
+            .collect();
+
+        println!("deadlock at {}..{}: {}", from, to, trains.join(", "));
+    }
+
     table.add_row(Row::new(vec![
         Cell::new("duration"),
         Cell::new(&format!("{:.3}s", duration as f64 / 1000.0)),
@@ -23,10 +62,78 @@ pub fn debug(model: Model, solution: Solution, duration: u128, checked_moves: us
             (checked_moves as f64 / duration as f64) as i32
         )),
     ]));
+    table.add_row(Row::new(vec![
+        Cell::new("iterations"),
+        Cell::new(&format!("{}", report.iterations)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("restarts"),
+        Cell::new(&format!("{}", report.restarts)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("improvements"),
+        Cell::new(&format!("{}", report.improvements.len())),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("termination"),
+        Cell::new(report.termination.name()),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("repairs"),
+        Cell::new(&format!("{}", report.repairs)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("deadlocks"),
+        Cell::new(&format!("{}", report.deadlocks)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("stalls"),
+        Cell::new(&format!("{}", report.stalls)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("removed moves"),
+        Cell::new(&format!("{}", report.removed_moves)),
+    ]));
     table.add_row(Row::new(vec![
         Cell::new("delays"),
         Cell::new(&format!("{}", solution.fitness())),
     ]));
+
+    // the bounds and the gap refer to the minimized objective
+    match objective {
+        Objective::TotalDelay => {
+            table.add_row(Row::new(vec![
+                Cell::new("delays lower bound"),
+                Cell::new(&format!("{}", delay_lower_bound(&model))),
+            ]));
+            table.add_row(Row::new(vec![
+                Cell::new("delays flow bound"),
+                Cell::new(&format!("{}", flow_lower_bound(&model))),
+            ]));
+        }
+        Objective::MaxDelay => {
+            table.add_row(Row::new(vec![
+                Cell::new("max delay"),
+                Cell::new(&format!("{}", fitness)),
+            ]));
+            table.add_row(Row::new(vec![
+                Cell::new("max delay lower bound"),
+                Cell::new(&format!("{}", objective.lower_bound(&model))),
+            ]));
+        }
+    }
+
+    table.add_row(Row::new(vec![
+        Cell::new("optimality gap"),
+        Cell::new(&format!(
+            "{} ({:.2}%)",
+            gap,
+            match fitness {
+                0 => 0.0,
+                fitness => gap as f64 / fitness as f64 * 100.0,
+            }
+        )),
+    ]));
     table.add_row(Row::new(vec![
         Cell::new("arrived passengers"),
         Cell::new(&format!(
@@ -35,10 +142,66 @@ pub fn debug(model: Model, solution: Solution, duration: u128, checked_moves: us
             model.passengers.len()
         )),
     ]));
+    table.add_row(Row::new(vec![
+        Cell::new("transfers"),
+        Cell::new(&format!("{}", solution.transfers(&model))),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("used trains"),
+        Cell::new(&format!(
+            "{}/{}",
+            solution.used_trains(),
+            model.trains.len()
+        )),
+    ]));
     table.add_row(Row::new(vec![
         Cell::new("t-max"),
         Cell::new(&format!("{}", model.t_max)),
     ]));
+    table.add_row(Row::new(vec![
+        Cell::new("tabu false positive rate"),
+        Cell::new(&format!("{:.4}%", report.tabu_false_positive_rate * 100.0)),
+    ]));
+
+    // improvements / restarts of each used restart strategy
+    for (strategy, statistics) in report
+        .restart_statistics
+        .iter()
+        .filter(|(_, s)| s.restarts > 0)
+    {
+        table.add_row(Row::new(vec![
+            Cell::new(&format!("restarts {}", strategy.name())),
+            Cell::new(&format!(
+                "{}/{}",
+                statistics.improvements, statistics.restarts
+            )),
+        ]));
+    }
+
+    table.printstd();
+}
+
+/// Prints how often each rule has returned a result and decided a chosen
+/// move, the rules are named by the given labels.
+pub fn debug_rules(statistics: &RuleStatistics, labels: &[String]) {
+    let mut table = Table::new();
+
+    table.add_row(Row::new(
+        ["rule", "true", "false", "none", "decided"]
+            .iter()
+            .map(|header| Cell::new(header))
+            .collect(),
+    ));
+
+    for i in 0..statistics.none.len() {
+        table.add_row(Row::new(vec![
+            Cell::new(&labels.get(i).cloned().unwrap_or(format!("rule {}", i))),
+            Cell::new(&format!("{}", statistics.some_true[i])),
+            Cell::new(&format!("{}", statistics.some_false[i])),
+            Cell::new(&format!("{}", statistics.none[i])),
+            Cell::new(&format!("{}", statistics.decided[i])),
+        ]));
+    }
 
     table.printstd();
 }
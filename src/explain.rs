@@ -0,0 +1,149 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::passenger::Id as PId;
+use crate::solution::Solution;
+use crate::train::Id as TId;
+use crate::types::Time;
+
+/// The rules that decided a move chosen by the search.
+#[derive(Clone)]
+pub struct Decision {
+    /// The point in time of the move.
+    pub t: Time,
+
+    /// The chosen move.
+    pub chosen: Move,
+
+    /// The best of the other moves that are not tabu, no move if there is
+    /// none.
+    pub runner_up: Move,
+
+    /// The index of the rule that decided the chosen move over the runner-up
+    /// and its decision, `None` if no rule decided.
+    pub over_runner_up: Option<(usize, bool)>,
+
+    /// The index of the rule that decided the chosen move over no move.
+    pub over_none: Option<(usize, bool)>,
+
+    /// The passengers that are moved or carried by the train.
+    pub passengers: Vec<PId>,
+}
+
+impl Decision {
+    /// Determines whether the chosen move is still part of the solution.
+    pub fn is_in(&self, solution: &Solution) -> bool {
+        solution
+            .0
+            .get(self.t)
+            .map_or(false, |state| state.moves.contains(&self.chosen))
+    }
+}
+
+/// The trains and passengers whose decisions are explained, all decisions
+/// are explained when both are empty.
+#[derive(Clone, Default)]
+pub struct Filter {
+    pub trains: Vec<TId>,
+    pub passengers: Vec<PId>,
+}
+
+impl Filter {
+    /// Determines whether the decision concerns one of the trains or one of
+    /// the passengers.
+    pub fn matches(&self, decision: &Decision) -> bool {
+        if self.trains.is_empty() && self.passengers.is_empty() {
+            return true;
+        }
+
+        decision
+            .chosen
+            .t_id()
+            .map_or(false, |t_id| self.trains.contains(&t_id))
+            || decision
+                .passengers
+                .iter()
+                .any(|p_id| self.passengers.contains(p_id))
+    }
+}
+
+/// Gets the label of the given rule, the index is used when no label is
+/// given.
+fn label(rule: Option<(usize, bool)>, labels: &[String]) -> String {
+    match rule {
+        Some((i, result)) => {
+            let name = labels.get(i).cloned().unwrap_or(format!("rule {}", i));
+
+            match result {
+                true => name,
+                false => format!("{} (against)", name),
+            }
+        }
+        None => "no rule".to_owned(),
+    }
+}
+
+/// Gets the per-tick log of the decisions that match the filter, the rules
+/// are named by the given labels, see
+/// [get_rule_labels](crate::rules::get_rule_labels).
+pub fn explain(
+    decisions: &[Decision],
+    filter: &Filter,
+    labels: &[String],
+    model: &Model,
+) -> String {
+    let mut decisions: Vec<&Decision> = decisions.iter().filter(|d| filter.matches(d)).collect();
+    let mut string: String = "".to_owned();
+    let mut time = Option::None;
+
+    decisions.sort_by_key(|decision| decision.t);
+
+    for decision in decisions {
+        if time != Some(decision.t) {
+            string.push_str(&format!("[Time:{}]\n", decision.t));
+            time = Some(decision.t);
+        }
+
+        string.push_str(&decision.chosen.to_string(model));
+
+        if let Move::None(_) = decision.runner_up {
+            string.push_str(" without runner-up");
+        } else {
+            string.push_str(&format!(
+                " over {} by {}",
+                decision.runner_up.to_string(model),
+                label(decision.over_runner_up, labels)
+            ));
+        }
+
+        string.push_str(&format!(
+            ", over no move by {}\n",
+            label(decision.over_none, labels)
+        ));
+    }
+
+    string
+}
+
+#[test]
+fn it_filters_decisions_by_passenger() {
+    use crate::move_::{Board, None};
+
+    let decision = Decision {
+        t: 1,
+        chosen: Move::Board(Board {
+            t_id: 0,
+            p_id: 1,
+            s_id: 0,
+        }),
+        runner_up: Move::None(None()),
+        over_runner_up: Option::None,
+        over_none: Some((0, true)),
+        passengers: vec![1],
+    };
+    let filter = |trains, passengers| Filter { trains, passengers };
+
+    assert!(filter(vec![], vec![]).matches(&decision));
+    assert!(filter(vec![], vec![1]).matches(&decision));
+    assert!(filter(vec![0], vec![]).matches(&decision));
+    assert!(!filter(vec![1], vec![0]).matches(&decision));
+}
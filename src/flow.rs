@@ -0,0 +1,122 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// An edge of the residual network, the reverse edge is located at the index
+/// of the edge xor 1.
+struct Edge {
+    to: usize,
+    capacity: i64,
+    cost: i64,
+}
+
+/// A min-cost flow solver via successive shortest paths, the shortest paths
+/// are found by Dijkstra's algorithm with node potentials, which requires
+/// non-negative edge costs.
+pub struct MinCostFlow {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl MinCostFlow {
+    /// Constructs a new network with the given number of nodes.
+    pub fn new(nodes: usize) -> MinCostFlow {
+        MinCostFlow {
+            edges: vec![],
+            adjacency: (0..nodes).map(|_| vec![]).collect(),
+        }
+    }
+
+    /// Adds a directed edge with the given capacity and non-negative cost per
+    /// unit of flow.
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: i64, cost: i64) {
+        self.adjacency[from].push(self.edges.len());
+        self.edges.push(Edge { to, capacity, cost });
+        self.adjacency[to].push(self.edges.len());
+        self.edges.push(Edge {
+            to: from,
+            capacity: 0,
+            cost: -cost,
+        });
+    }
+
+    /// Sends at most `max_flow` units from the source to the sink at minimum
+    /// cost, the flow and the cost are returned.
+    pub fn run(&mut self, source: usize, sink: usize, max_flow: i64) -> (i64, i64) {
+        let nodes = self.adjacency.len();
+        let mut potentials = vec![0; nodes];
+        let mut flow = 0;
+        let mut cost = 0;
+
+        while flow < max_flow {
+            let mut distances = vec![i64::MAX; nodes];
+            let mut parents: Vec<Option<usize>> = vec![None; nodes];
+            let mut heap = BinaryHeap::new();
+
+            distances[source] = 0;
+            heap.push(Reverse((0, source)));
+
+            while let Some(Reverse((distance, node))) = heap.pop() {
+                if distance > distances[node] {
+                    continue;
+                }
+
+                for &e in &self.adjacency[node] {
+                    let edge = &self.edges[e];
+                    let reduced = edge.cost + potentials[node] - potentials[edge.to];
+
+                    if edge.capacity > 0 && distance + reduced < distances[edge.to] {
+                        distances[edge.to] = distance + reduced;
+                        parents[edge.to] = Some(e);
+                        heap.push(Reverse((distances[edge.to], edge.to)));
+                    }
+                }
+            }
+
+            if distances[sink] == i64::MAX {
+                break;
+            }
+
+            for (potential, distance) in potentials.iter_mut().zip(distances.iter()) {
+                if *distance < i64::MAX {
+                    *potential += distance;
+                }
+            }
+
+            // the bottleneck of the shortest path
+            let mut push = max_flow - flow;
+            let mut node = sink;
+
+            while let Some(e) = parents[node] {
+                push = push.min(self.edges[e].capacity);
+                node = self.edges[e ^ 1].to;
+            }
+
+            node = sink;
+
+            while let Some(e) = parents[node] {
+                self.edges[e].capacity -= push;
+                self.edges[e ^ 1].capacity += push;
+                cost += push * self.edges[e].cost;
+                node = self.edges[e ^ 1].to;
+            }
+
+            flow += push;
+        }
+
+        (flow, cost)
+    }
+}
+
+#[test]
+fn it_sends_the_flow_along_the_cheapest_paths() {
+    let mut network = MinCostFlow::new(4);
+
+    network.add_edge(0, 1, 2, 1);
+    network.add_edge(0, 2, 1, 2);
+    network.add_edge(1, 3, 1, 1);
+    network.add_edge(1, 2, 1, 0);
+    network.add_edge(2, 3, 2, 3);
+
+    // 0-1-3 costs 2, 0-1-2-3 costs 4 and 0-2-3 costs 5
+    assert_eq!(network.run(0, 3, 3), (3, 11));
+}
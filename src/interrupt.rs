@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether an interrupt signal has been received.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle(signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+
+    // a second signal terminates the process immediately
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+    }
+}
+
+/// Installs handlers for SIGINT and SIGTERM, so that a running search stops
+/// gracefully instead of terminating the process.
+pub fn install() {
+    let handler = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// Determines whether an interrupt signal has been received.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -1,15 +1,31 @@
+pub mod bound;
+pub mod checkpoint;
+pub mod cleanup;
 pub mod connection;
 pub mod debug;
+pub mod explain;
+pub mod flow;
+pub mod interrupt;
+pub mod matching;
 pub mod model;
 pub mod move_;
 pub mod parser;
 pub mod passenger;
+pub mod planner;
 pub mod plotter;
+pub mod portfolio;
+pub mod repair;
+pub mod restart;
+pub mod rollout;
 pub mod rule;
 pub mod rules;
+pub mod score;
+pub mod search;
 pub mod solution;
 pub mod state;
 pub mod station;
 pub mod tabu;
+pub mod tabu_list;
 pub mod train;
+pub mod tuning;
 pub mod types;
@@ -1,11 +1,28 @@
 #![warn(unused_extern_crates)]
-use clap::{App, Arg, ArgMatches};
-use rstrain::debug::debug;
-use rstrain::parser::parse;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rstrain::checkpoint::Checkpoint;
+use rstrain::debug::{debug, debug_rules};
+use rstrain::explain::{explain, Filter};
+use rstrain::interrupt;
+use rstrain::matching::assign;
+use rstrain::parser::{parse, parse_timetable};
+use rstrain::planner::Planner;
 use rstrain::plotter::Plotter;
+use rstrain::portfolio::{race, Features, Portfolio};
+use rstrain::restart::Strategy;
+use rstrain::rules::{
+    get_rule_labels, get_rules_by_names, parse_rule_set_names, parse_rule_sets, requires_deadlocks,
+    DEFAULT_RULE_SETS,
+};
+use rstrain::score::{default_rules, parse_weights, Ranking, Scoring};
+use rstrain::search::{Objective, SearchConfig, TabuBackend};
+use rstrain::solution::Solution;
 use rstrain::tabu::TabuGeneticSearch;
+use rstrain::tuning::{tune, TuningConfig};
+use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::process;
 use std::str::FromStr;
 
 fn get_std_in() -> String {
@@ -32,6 +49,129 @@ fn parse_arg<T: FromStr>(
         .parse()
 }
 
+/// Parses a number of bytes with an optional unit, e.g. `512M`.
+fn parse_memory(string: &str) -> Result<usize, String> {
+    let (number, unit) = match string.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => string.split_at(i),
+        None => (string, ""),
+    };
+    let factor = match unit.to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(format!("Invalid memory size \"{}\"!", string)),
+    };
+
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .ok_or(format!("Invalid memory size \"{}\"!", string))
+}
+
+/// Gets the ids of the comma separated names, e.g. of trains or passengers.
+fn parse_names(string: &str, names: &[&str]) -> Result<Vec<usize>, String> {
+    string
+        .split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            names
+                .iter()
+                .position(|other| *other == name)
+                .ok_or(format!("Unknown name \"{}\"!", name))
+        })
+        .collect()
+}
+
+/// Reads the instances of the directory, which are either files or the
+/// `input.txt` of sub directories like in `test-cases`.
+fn read_instances(path: &str) -> Result<Vec<(String, String)>, String> {
+    let mut instances = vec![];
+    let mut entries: Vec<_> = fs::read_dir(path)
+        .map_err(|e| format!("Failed to read instances \"{}\": {}", path, e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+
+    entries.sort();
+
+    for entry in entries {
+        let file = match entry.is_dir() {
+            true => entry.join("input.txt"),
+            false => entry,
+        };
+
+        if let Ok(string) = fs::read_to_string(&file) {
+            instances.push((file.display().to_string(), string));
+        }
+    }
+
+    Ok(instances)
+}
+
+/// Tunes the rule sets and the weights on the instances of a directory and
+/// writes the best configuration as rules file.
+fn run_tune(matches: &ArgMatches) {
+    let config = TuningConfig {
+        runs: parse_arg(matches, "RUNS", "3").unwrap(),
+        max_millis: parse_arg(matches, "TIME", "1000").unwrap(),
+        max_checked_moves: matches
+            .value_of("MAX_CHECKED_MOVES")
+            .map(|max| max.parse().unwrap()),
+        candidates: parse_arg(matches, "CANDIDATES", "20").unwrap(),
+        seed: parse_arg(matches, "SEED", "0").unwrap(),
+    };
+
+    let instances = match read_instances(matches.value_of("INSTANCES").unwrap()) {
+        Ok(instances) if !instances.is_empty() => instances,
+        Ok(_) => {
+            eprintln!("No instances found!");
+            process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+
+    eprintln!("[tune] {} instances", instances.len());
+
+    let models: Vec<_> = instances.iter().map(|(_, string)| parse(string)).collect();
+    let evaluations = tune(&models, &config, |evaluation| {
+        eprintln!(
+            "[tune] success rate {:.2}, mean delay {:.2}: {}{}",
+            evaluation.success_rate,
+            evaluation.mean_delay,
+            evaluation.rule_sets.join(","),
+            match &evaluation.weights {
+                Some(weights) => format!(
+                    " weights {}",
+                    weights
+                        .iter()
+                        .map(|weight| weight.to_string())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                ),
+                None => String::new(),
+            }
+        );
+    });
+
+    let rules_file = evaluations[0].to_rules_file();
+
+    match matches.value_of("OUTPUT") {
+        Some(path) => {
+            if let Err(error) = fs::write(path, rules_file) {
+                eprintln!("Failed to write rules \"{}\": {}", path, error);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", rules_file),
+    }
+}
+
 fn main() {
     let matches = App::new("rstrain")
         .version("0.0.1")
@@ -46,6 +186,19 @@ fn main() {
                     "Size of tabu list, increase for large models (default 8000000)",
                 ),
         )
+        .arg(
+            Arg::with_name("TABU_MEMORY")
+                .long("tabu-memory")
+                .takes_value(true)
+                .help("Memory budget of the tabu list, e.g. 512M, overrides the tabu size"),
+        )
+        .arg(
+            Arg::with_name("TABU_BACKEND")
+                .long("tabu-backend")
+                .takes_value(true)
+                .possible_values(&["exact", "bloom"])
+                .help("Stores the tabu list exactly or in a rotating Bloom filter with false positives (default exact)"),
+        )
         .arg(
             Arg::with_name("TIME")
                 .short("t")
@@ -60,6 +213,36 @@ fn main() {
                 .takes_value(true)
                 .help("Max number of iterations without improvement (default 20000)"),
         )
+        .arg(
+            Arg::with_name("TARGET_FITNESS")
+                .long("target-fitness")
+                .takes_value(true)
+                .help("Stops the search as soon as the fitness is less or equal to the given value"),
+        )
+        .arg(
+            Arg::with_name("MAX_CHECKED_MOVES")
+                .long("max-compared-moves")
+                .takes_value(true)
+                .help("Stops the search after the given number of compared moves, which is independent of the machine"),
+        )
+        .arg(
+            Arg::with_name("MAX_RESTARTS")
+                .long("max-restarts")
+                .takes_value(true)
+                .help("Stops the search after the given number of restarts"),
+        )
+        .arg(
+            Arg::with_name("STAGNATION")
+                .long("stagnation")
+                .takes_value(true)
+                .help("Stops the search when the fitness has improved less than the stagnation improvement within the given milliseconds"),
+        )
+        .arg(
+            Arg::with_name("STAGNATION_IMPROVEMENT")
+                .long("stagnation-improvement")
+                .takes_value(true)
+                .help("The minimal improvement in percent within the stagnation window (default 1)"),
+        )
         .arg(
             Arg::with_name("DEBUG")
                 .short("d")
@@ -74,6 +257,18 @@ fn main() {
                 .takes_value(true)
                 .help("The latest time, increase when a solution with a total delay of 0 cannot be found, default value is the latest arrival time of all passengers"),
         )
+        .arg(
+            Arg::with_name("TMAX_LIMIT")
+                .long("t-max-limit")
+                .takes_value(true)
+                .help("Raises t-max step by step up to the given limit as long as passengers have not arrived"),
+        )
+        .arg(
+            Arg::with_name("TMAX_STEP")
+                .long("t-max-step")
+                .takes_value(true)
+                .help("The step t-max is raised by (default 10% of the latest arrival time)"),
+        )
         .arg(
             Arg::with_name("PLOT")
                 .short("p")
@@ -81,37 +276,646 @@ fn main() {
                 .takes_value(false)
                 .help("Plots the fitness progress, plots are located in ./plots"),
         )
+        .arg(
+            Arg::with_name("PROGRESS")
+                .long("progress")
+                .takes_value(false)
+                .help("Prints the progress to stderr whenever the best solution is improved"),
+        )
+        .arg(
+            Arg::with_name("ADAPT_TRAINS")
+                .short("a")
+                .long("adapt-trains")
+                .takes_value(false)
+                .help("Reduces the number of used trains as long as the best delay is reached"),
+        )
+        .arg(
+            Arg::with_name("SEED")
+                .long("seed")
+                .takes_value(true)
+                .help("Seed of the random generator, makes searches reproducible"),
+        )
+        .arg(
+            Arg::with_name("OBJECTIVE")
+                .long("objective")
+                .takes_value(true)
+                .possible_values(&["total-delay", "max-delay"])
+                .help("The objective that is minimized (default total-delay)"),
+        )
+        .arg(
+            Arg::with_name("ROLLOUT")
+                .long("rollout")
+                .takes_value(true)
+                .help("Rolls out the given number of best moves of each train and chooses the move with the lowest estimated delay"),
+        )
+        .arg(
+            Arg::with_name("ROLLOUT_DEPTH")
+                .long("rollout-depth")
+                .takes_value(true)
+                .help("The number of points in time each move is rolled out (default 8)"),
+        )
+        .arg(
+            Arg::with_name("RULES")
+                .long("rules")
+                .takes_value(true)
+                .help("Comma separated rule sets in the order they are applied, e.g. avoid_station_overload,detrain_arrived_passenger"),
+        )
+        .arg(
+            Arg::with_name("RULES_FILE")
+                .long("rules-file")
+                .takes_value(true)
+                .conflicts_with("RULES")
+                .help("Reads the rule sets from the given file, separated by commas or new lines"),
+        )
+        .arg(
+            Arg::with_name("EXPLAIN")
+                .long("explain")
+                .takes_value(true)
+                .help("Writes the rules that decided each move of the timetable over the runner-up and over no move to the given file"),
+        )
+        .arg(
+            Arg::with_name("EXPLAIN_TRAINS")
+                .long("explain-trains")
+                .takes_value(true)
+                .requires("EXPLAIN")
+                .help("Comma separated trains whose moves are explained, e.g. T1,T2"),
+        )
+        .arg(
+            Arg::with_name("EXPLAIN_PASSENGERS")
+                .long("explain-passengers")
+                .takes_value(true)
+                .requires("EXPLAIN")
+                .help("Comma separated passengers whose boardings, detrains and departures are explained, e.g. P1"),
+        )
+        .arg(
+            Arg::with_name("RULE_STATISTICS")
+                .long("rule-statistics")
+                .takes_value(true)
+                .help("Writes how often each rule has returned true, false or no result and decided a move as JSON to the given file"),
+        )
+        .arg(
+            Arg::with_name("PORTFOLIO")
+                .long("portfolio")
+                .takes_value(true)
+                .conflicts_with_all(&["RULES", "RULES_FILE"])
+                .help("Selects the rule sets of the instance family in the given portfolio file whose features are closest to the model"),
+        )
+        .arg(
+            Arg::with_name("RACE")
+                .long("race")
+                .takes_value(true)
+                .requires("PORTFOLIO")
+                .conflicts_with_all(&["RULES", "RULES_FILE", "WARM_START", "PLAN", "MATCHING", "RESUME"])
+                .help("Races the rule sets of the portfolio in parallel for the given milliseconds and continues with the winner"),
+        )
+        .arg(
+            Arg::with_name("RANKING")
+                .long("ranking")
+                .takes_value(true)
+                .possible_values(&["lexicographic", "weighted"])
+                .conflicts_with_all(&["RULES", "RULES_FILE", "PORTFOLIO", "RACE", "ROLLOUT", "EXPLAIN", "RULE_STATISTICS"])
+                .help("Ranks the moves by the scores of the score rules instead of comparing them pairwise by the rules"),
+        )
+        .arg(
+            Arg::with_name("WEIGHTS")
+                .long("weights")
+                .takes_value(true)
+                .requires("RANKING")
+                .help("Comma separated weights of the score rules for the weighted ranking, one per rule (default 256,128,...,1)"),
+        )
+        .arg(
+            Arg::with_name("RESTART")
+                .long("restart")
+                .takes_value(true)
+                .possible_values(&["uniform", "first-delay", "capacity-conflict", "late-biased", "mixed"])
+                .help("Strategy to choose the point in time the search is restarted from (default uniform)"),
+        )
+        .arg(
+            Arg::with_name("CHECKPOINT")
+                .short("c")
+                .long("checkpoint")
+                .takes_value(true)
+                .help("Periodically writes a checkpoint of the search to the given file"),
+        )
+        .arg(
+            Arg::with_name("CHECKPOINT_INTERVAL")
+                .long("checkpoint-interval")
+                .takes_value(true)
+                .help("Milliseconds between two checkpoints (default 60000)"),
+        )
+        .arg(
+            Arg::with_name("WARM_START")
+                .short("w")
+                .long("warm-start")
+                .takes_value(true)
+                .help("Starts the search from the timetable in the given output file"),
+        )
+        .arg(
+            Arg::with_name("MAX_REPAIRS")
+                .long("max-repairs")
+                .takes_value(true)
+                .help("Repairs of overloaded stations until the search is restarted (default 8)"),
+        )
+        .arg(
+            Arg::with_name("MAX_STALL")
+                .long("max-stall")
+                .takes_value(true)
+                .help("Points in time without boarding or detraining until a run is restarted as stuck"),
+        )
+        .arg(
+            Arg::with_name("NO_CLEAN_UP")
+                .long("no-clean-up")
+                .takes_value(false)
+                .help("Keeps wasted moves like unused trains and empty round trips in the timetable"),
+        )
+        .arg(
+            Arg::with_name("PLAN")
+                .long("plan")
+                .takes_value(false)
+                .conflicts_with("WARM_START")
+                .help("Starts the search from the timetable of the reservation planner"),
+        )
+        .arg(
+            Arg::with_name("MATCHING")
+                .long("matching")
+                .takes_value(false)
+                .conflicts_with_all(&["WARM_START", "PLAN"])
+                .help("Starts the search from the starts and first pickups of a min-cost matching of passengers to trains"),
+        )
+        .arg(
+            Arg::with_name("PLAN_ONLY")
+                .long("plan-only")
+                .takes_value(false)
+                .help("Prints the timetable of the reservation planner without searching"),
+        )
+        .arg(
+            Arg::with_name("RESUME")
+                .short("r")
+                .long("resume")
+                .takes_value(true)
+                .help("Resumes the search from the given checkpoint file, requires the same model"),
+        )
+        .subcommand(
+            SubCommand::with_name("tune")
+                .about("Tunes the rule sets and the weights of the score rules on a directory of instances")
+                .arg(
+                    Arg::with_name("INSTANCES")
+                        .required(true)
+                        .help("The directory of the instances, files or sub directories with an input.txt"),
+                )
+                .arg(
+                    Arg::with_name("RUNS")
+                        .long("runs")
+                        .takes_value(true)
+                        .help("Seeded searches of each configuration on each instance (default 3)"),
+                )
+                .arg(
+                    Arg::with_name("TIME")
+                        .short("t")
+                        .long("time")
+                        .takes_value(true)
+                        .help("Max duration of each search in milliseconds (default 1000)"),
+                )
+                .arg(
+                    Arg::with_name("MAX_CHECKED_MOVES")
+                        .long("max-compared-moves")
+                        .takes_value(true)
+                        .help("Stops each search after the given number of compared moves"),
+                )
+                .arg(
+                    Arg::with_name("CANDIDATES")
+                        .long("candidates")
+                        .takes_value(true)
+                        .help("The number of evaluated rule configurations (default 20)"),
+                )
+                .arg(
+                    Arg::with_name("SEED")
+                        .long("seed")
+                        .takes_value(true)
+                        .help("Seed of the changes of the rule configurations (default 0)"),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Writes the best rule configuration to the given file, which can be passed to --rules-file"),
+                ),
+        )
         .get_matches();
 
+    if let Some(matches) = matches.subcommand_matches("tune") {
+        run_tune(matches);
+        return;
+    }
+
     // parse arguments
     let max_millis = parse_arg(&matches, "TIME", "600000").unwrap();
     let max_iterations = parse_arg(&matches, "ITERATIONS", "20000").unwrap();
     let tabu_size = parse_arg(&matches, "TABU", "8000000").unwrap();
     let t_max = parse_arg(&matches, "TMAX", "0").unwrap();
+    let t_max_limit = parse_arg(&matches, "TMAX_LIMIT", "0").unwrap();
     let track_fitness = matches.is_present("PLOT");
+    let checkpoint_interval = parse_arg(&matches, "CHECKPOINT_INTERVAL", "60000").unwrap();
+    let restart: Strategy = parse_arg(&matches, "RESTART", "uniform").unwrap();
+    let objective: Objective = parse_arg(&matches, "OBJECTIVE", "total-delay").unwrap();
+    let max_repairs = parse_arg(&matches, "MAX_REPAIRS", "8").unwrap();
 
     // build model
-    let mut model = parse(&get_std_in());
+    let input = get_std_in();
+    let mut model = parse(&input);
     model.t_max = std::cmp::max(model.t_max, t_max);
 
+    let rule_sets = match (matches.value_of("RULES"), matches.value_of("RULES_FILE")) {
+        (Some(rules), _) => Some(Ok(rules.to_owned())),
+        (_, Some(path)) => Some(
+            fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read rules \"{}\": {}", path, e)),
+        ),
+        _ => None,
+    };
+
+    // the weights of the weighted ranking given by the rules file
+    let mut rules_weights = None;
+
+    // the deadlocks are detected for the rules and the report only
+    let mut detect_deadlocks = matches.is_present("DEBUG");
+
+    // the labels of the rules in explained decisions
+    let mut rule_labels = match rule_sets {
+        Some(Ok(rule_sets)) => match parse_rule_sets(&rule_sets)
+            .and_then(|rules| parse_weights(&rule_sets).map(|weights| (rules, weights)))
+        {
+            Ok((rules, weights)) => {
+                model.rules = rules;
+                rules_weights = weights;
+                detect_deadlocks |= requires_deadlocks(&parse_rule_set_names(&rule_sets));
+                get_rule_labels(&parse_rule_set_names(&rule_sets))
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        },
+        Some(Err(error)) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        None => get_rule_labels(&DEFAULT_RULE_SETS),
+    };
+
+    let default_t_max_step = (model.max_arrival / 10).max(1).to_string();
+    let t_max_step = matches
+        .value_of("TMAX_STEP")
+        .unwrap_or(&default_t_max_step)
+        .parse()
+        .unwrap();
+
+    if matches.is_present("PLAN_ONLY") {
+        let solution = Planner::new(&model).plan();
+
+        println!(
+            "{}",
+            solution.to_string(&model, matches.is_present("DEBUG"))
+        );
+        return;
+    }
+
+    let portfolio = matches.value_of("PORTFOLIO").map(|path| {
+        match fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read portfolio \"{}\": {}", path, e))
+            .and_then(|string| Portfolio::parse(&string))
+        {
+            Ok(portfolio) => portfolio,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+    });
+
+    // the rule sets of the closest instance family are used, unless the rule
+    // sets are raced
+    if let (Some(portfolio), false) = (&portfolio, matches.is_present("RACE")) {
+        let features = Features::of(&model);
+        let rule_sets = portfolio.select(&features);
+
+        eprintln!("[portfolio] {}, selected {}", features, rule_sets.join(","));
+        model.rules = get_rules_by_names(&rule_sets).unwrap();
+        rule_labels = get_rule_labels(&rule_sets);
+        detect_deadlocks |= requires_deadlocks(&rule_sets);
+    }
+
+    // the winner of the race may be any rule configuration of the portfolio
+    if let (Some(portfolio), true) = (&portfolio, matches.is_present("RACE")) {
+        detect_deadlocks |= portfolio.0.iter().any(|e| requires_deadlocks(&e.rule_sets));
+    }
+
+    let mut filter = Filter::default();
+    let train_names: Vec<&str> = model.trains.iter().map(|t| t.name.as_str()).collect();
+    let passenger_names: Vec<&str> = model.passengers.iter().map(|p| p.name.as_str()).collect();
+
+    for (arg, names, ids) in [
+        ("EXPLAIN_TRAINS", &train_names, &mut filter.trains),
+        (
+            "EXPLAIN_PASSENGERS",
+            &passenger_names,
+            &mut filter.passengers,
+        ),
+    ] {
+        if let Some(string) = matches.value_of(arg) {
+            match parse_names(string, names) {
+                Ok(parsed) => *ids = parsed,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    // the moves are ranked by the score rules instead of the rules, whose
+    // decisions cannot be explained or counted
+    let ranked = matches.is_present("RANKING") || rules_weights.is_some();
+
+    if rules_weights.is_some() && matches.is_present("ROLLOUT") {
+        eprintln!("The rollout cannot be used with the weights of the rules file!");
+        process::exit(1);
+    }
+    if rules_weights.is_some()
+        && (matches.is_present("EXPLAIN") || matches.is_present("RULE_STATISTICS"))
+    {
+        eprintln!("The decisions of the weighted ranking of the rules file cannot be explained or counted!");
+        process::exit(1);
+    }
+
     // construct TabuGeneticSearch
-    let mut tabu = TabuGeneticSearch::new(max_millis, max_iterations, tabu_size, track_fitness);
+    let mut config = SearchConfig::default()
+        .time(max_millis)
+        .iterations(max_iterations)
+        .tabu_size(tabu_size)
+        .objective(objective)
+        .restart(restart)
+        .adapt_trains(matches.is_present("ADAPT_TRAINS"))
+        .max_repairs(max_repairs)
+        .clean_up(!matches.is_present("NO_CLEAN_UP"))
+        .explain(matches.is_present("EXPLAIN"))
+        .rule_statistics(
+            !ranked && (matches.is_present("DEBUG") || matches.is_present("RULE_STATISTICS")),
+        )
+        .detect_deadlocks(detect_deadlocks)
+        .track_fitness(track_fitness);
+
+    if let Some(memory) = matches.value_of("TABU_MEMORY") {
+        match parse_memory(memory) {
+            Ok(memory) => config = config.tabu_memory(memory),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+    }
+
+    if matches.value_of("TABU_BACKEND") == Some("bloom") {
+        config = config.tabu_backend(TabuBackend::Bloom);
+    }
+
+    if let Some(seed) = matches.value_of("SEED") {
+        match seed.parse() {
+            Ok(seed) => config = config.seed(seed),
+            Err(_) => {
+                eprintln!("Invalid seed \"{}\"!", seed);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(width) = matches.value_of("ROLLOUT") {
+        let depth = parse_arg(&matches, "ROLLOUT_DEPTH", "8");
+
+        match (width.parse(), depth) {
+            (Ok(width), Ok(depth)) if width >= 1 && depth >= 1 => {
+                config = config.rollout(width, depth);
+            }
+            _ => {
+                eprintln!("The rollout width and depth have to be at least 1!");
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(ranking) = matches.value_of("RANKING") {
+        let mut ranking = Ranking::from_str(ranking).unwrap();
+
+        if let Some(weights) = matches.value_of("WEIGHTS") {
+            let weights: Result<Vec<f64>, _> =
+                weights.split(',').map(|w| w.trim().parse()).collect();
+
+            match (weights, &ranking) {
+                (Ok(weights), Ranking::Weighted(_)) if weights.len() == default_rules().len() => {
+                    ranking = Ranking::Weighted(weights);
+                }
+                (Ok(_), Ranking::Weighted(_)) => {
+                    eprintln!(
+                        "Expected {} weights, one per score rule!",
+                        default_rules().len()
+                    );
+                    process::exit(1);
+                }
+                (Ok(_), _) => {
+                    eprintln!("Weights require the weighted ranking!");
+                    process::exit(1);
+                }
+                (Err(_), _) => {
+                    eprintln!("Invalid weights!");
+                    process::exit(1);
+                }
+            }
+        }
+
+        config = config.scoring(Scoring::new(ranking));
+    } else if let Some(weights) = rules_weights {
+        config = config.scoring(Scoring::new(Ranking::Weighted(weights)));
+    }
+
+    if let Some(target) = matches.value_of("TARGET_FITNESS") {
+        config = config.target_fitness(target.parse().unwrap());
+    }
+
+    if let Some(max) = matches.value_of("MAX_CHECKED_MOVES") {
+        config = config.max_checked_moves(max.parse().unwrap());
+    }
+
+    if let Some(max) = matches.value_of("MAX_RESTARTS") {
+        config = config.max_restarts(max.parse().unwrap());
+    }
+
+    if let Some(max) = matches.value_of("MAX_STALL") {
+        config = config.max_stall(max.parse().unwrap());
+    }
+
+    if let Some(window) = matches.value_of("STAGNATION") {
+        let improvement: f64 = parse_arg(&matches, "STAGNATION_IMPROVEMENT", "1").unwrap();
+
+        config = config.stagnation(window.parse().unwrap(), improvement / 100.0);
+    }
+
+    let mut tabu = TabuGeneticSearch::new(config);
+
+    if matches.is_present("PROGRESS") {
+        let passengers = model.passengers.len();
+
+        tabu.observe(move |progress| {
+            eprintln!(
+                "[Iteration:{}] fitness: {}, arrived passengers: {}/{}, elapsed: {:.3}s",
+                progress.iteration,
+                progress.fitness,
+                progress.arrived,
+                passengers,
+                progress.elapsed as f64 / 1000.0
+            );
+        });
+    }
+
+    if let Some(path) = matches.value_of("CHECKPOINT") {
+        tabu.checkpoint(path, checkpoint_interval);
+    }
+
+    if let Some(path) = matches.value_of("WARM_START") {
+        let timetable = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read timetable \"{}\": {}", path, e))
+            .and_then(|string| parse_timetable(&string, &model));
+
+        match timetable {
+            Ok(timetable) => {
+                let solution = Solution::from_timetable(&model, &timetable);
+
+                if solution.0.len() < timetable.len() {
+                    eprintln!(
+                        "The timetable is illegal at t = {}, only the legal prefix is used",
+                        solution.0.len()
+                    );
+                }
+
+                tabu.warm_start(solution);
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(millis) = matches.value_of("RACE") {
+        // the race requires a portfolio
+        let candidates: Vec<Vec<String>> = portfolio
+            .iter()
+            .flat_map(|portfolio| portfolio.0.iter().map(|e| e.rule_sets.clone()))
+            .collect();
+
+        if candidates.is_empty() {
+            eprintln!("No rule configurations to race!");
+            process::exit(1);
+        }
+
+        let t_max = model.t_max;
+        let runs = race(
+            || {
+                let mut model = parse(&input);
+
+                model.t_max = t_max;
+                model
+            },
+            &candidates,
+            millis.parse().unwrap(),
+            tabu.config().seed,
+            tabu.config().objective,
+        );
+
+        for run in &runs {
+            eprintln!(
+                "[race] fitness {}: {}",
+                run.fitness,
+                run.rule_sets.join(",")
+            );
+        }
+
+        // the search continues with the rule sets and the solution of the
+        // winner
+        model.rules = get_rules_by_names(&runs[0].rule_sets).unwrap();
+        rule_labels = get_rule_labels(&runs[0].rule_sets);
+        tabu.warm_start(Solution::replay(&model, &runs[0].moves));
+    }
+
+    if matches.is_present("PLAN") {
+        tabu.warm_start(Planner::new(&model).plan());
+    }
+
+    if matches.is_present("MATCHING") {
+        let mut planner = Planner::new(&model);
+
+        planner.pick_up(&assign(&model));
+        tabu.warm_start(planner.solution());
+    }
+
+    if let Some(path) = matches.value_of("RESUME") {
+        match Checkpoint::read(path, &model) {
+            Ok(checkpoint) => {
+                // the search continues with the t_max it has been raised to
+                model.t_max = model.t_max.max(checkpoint.t_max);
+                tabu.resume(checkpoint);
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+    }
+
+    // stop the search on SIGINT/SIGTERM and print the best solution found so far
+    interrupt::install();
 
     // run tabu-enhanced genetic search
-    let (solution, duration) = tabu.search(&model);
+    let report = if t_max_limit > model.t_max {
+        tabu.search_escalating(&mut model, t_max_step, t_max_limit)
+    } else {
+        tabu.search(&model)
+    };
+
+    if let Some(path) = matches.value_of("EXPLAIN") {
+        let trace = explain(&report.decisions, &filter, &rule_labels, &model);
+
+        if let Err(error) = fs::write(path, trace) {
+            eprintln!("Failed to write explanation \"{}\": {}", path, error);
+            process::exit(1);
+        }
+    }
+
+    if let (Some(path), Some(statistics)) =
+        (matches.value_of("RULE_STATISTICS"), &report.rule_statistics)
+    {
+        if let Err(error) = fs::write(path, statistics.to_json(&rule_labels)) {
+            eprintln!("Failed to write rule statistics \"{}\": {}", path, error);
+            process::exit(1);
+        }
+    }
 
     // print result
     if matches.is_present("DEBUG") {
-        debug(model, solution, duration, tabu.checked_moves);
+        debug(model, &report, objective);
+
+        if let Some(statistics) = &report.rule_statistics {
+            debug_rules(statistics, &rule_labels);
+        }
     } else {
-        println!("{}", solution.to_string(&model, false));
+        println!("{}", report.solution.to_string(&model, false));
     }
 
     // plot fitness
     if matches.is_present("PLOT") {
         #[allow(unused_must_use)]
         {
-            (Plotter { path: "plots" }).plot_fitness(&tabu);
+            (Plotter { path: "plots" }).plot_fitness(&report);
         }
     }
 }
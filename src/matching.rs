@@ -0,0 +1,288 @@
+use crate::model::Model;
+use crate::passenger::Id as PId;
+use crate::station::Id as SId;
+use crate::train::{Id as TId, StartStation};
+use crate::types::Time;
+use fxhash::FxHashMap;
+
+/// The cost of a train that cannot carry any passenger group of a cluster.
+const INFEASIBLE: i64 = 1 << 40;
+
+/// The weight of each point in time a cluster is expected to arrive late.
+const LATENESS_WEIGHT: i64 = 10;
+
+/// Passenger groups with the same start and destination station, ordered by
+/// the arrival time.
+pub struct Cluster {
+    pub start: SId,
+    pub destination: SId,
+    pub passengers: Vec<PId>,
+}
+
+/// The cluster that is picked up first by a train.
+pub struct Assignment {
+    pub t_id: TId,
+
+    /// The passenger groups of the cluster that fit into the train.
+    pub passengers: Vec<PId>,
+
+    /// The station the passengers are picked up.
+    pub pickup: SId,
+}
+
+/// Groups the passengers by the start and destination station.
+pub fn clusters(model: &Model) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = vec![];
+    let mut index: FxHashMap<(SId, SId), usize> = FxHashMap::default();
+
+    for (p_id, passenger) in model.passengers.iter().enumerate() {
+        let key = (passenger.start, passenger.destination);
+        let i = *index.entry(key).or_insert_with(|| {
+            clusters.push(Cluster {
+                start: passenger.start,
+                destination: passenger.destination,
+                passengers: vec![],
+            });
+            clusters.len() - 1
+        });
+
+        clusters[i].passengers.push(p_id);
+    }
+
+    for cluster in clusters.iter_mut() {
+        cluster
+            .passengers
+            .sort_by_key(|&p_id| model.passengers[p_id].arrival);
+    }
+
+    clusters
+}
+
+/// Gets the passenger groups of the cluster that fit into the train, the
+/// earliest arrival times are loaded first.
+fn load(cluster: &Cluster, t_id: TId, model: &Model) -> Vec<PId> {
+    let mut capacity = model.trains[t_id].capacity;
+
+    cluster
+        .passengers
+        .iter()
+        .filter(|&&p_id| {
+            let size = model.passengers[p_id].size;
+
+            if size <= capacity {
+                capacity -= size;
+                true
+            } else {
+                false
+            }
+        })
+        .copied()
+        .collect()
+}
+
+/// Gets the cost of the train picking up the cluster first. The cost is
+/// composed of the travel time to the pickup, the expected lateness and the
+/// slack of the most urgent passenger group, and the unused capacity.
+fn cost(cluster: &Cluster, t_id: TId, model: &Model) -> i64 {
+    let passengers = load(cluster, t_id, model);
+
+    if passengers.is_empty() {
+        return INFEASIBLE;
+    }
+
+    let pickup = match model.trains[t_id].start {
+        StartStation::Station(s_id) => model.travel_time(t_id, s_id, cluster.start),
+        StartStation::Any => 0,
+    };
+
+    // boarding all groups and departing takes one point in time each
+    let arrival = pickup
+        .saturating_add(passengers.len() + 1)
+        .saturating_add(model.travel_time(t_id, cluster.start, cluster.destination));
+
+    if arrival > model.t_max {
+        return INFEASIBLE;
+    }
+
+    let deadline = model.passengers[passengers[0]].arrival;
+    let lateness = arrival.saturating_sub(deadline) as i64;
+    let slack = deadline.saturating_sub(arrival) as i64;
+    let loaded: i64 = passengers
+        .iter()
+        .map(|&p_id| model.passengers[p_id].size as i64)
+        .sum();
+
+    pickup as i64
+        + lateness * LATENESS_WEIGHT
+        + slack
+        + (model.trains[t_id].capacity as i64 - loaded)
+}
+
+/// Solves the min-cost assignment of the rows to the columns via the
+/// Hungarian method, which requires at most as many rows as columns. The
+/// column of each row is returned.
+pub fn hungarian(costs: &[Vec<i64>]) -> Vec<usize> {
+    let n = costs.len();
+    let m = costs.first().map_or(0, |row| row.len());
+
+    // potentials and the matched row of each column, 1-based with a virtual
+    // column 0
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; m + 1];
+    let mut matched = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+
+    for i in 1..=n {
+        matched[0] = i;
+
+        let mut j0 = 0;
+        let mut min = vec![i64::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+
+        loop {
+            used[j0] = true;
+
+            let i0 = matched[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+
+            for j in 1..=m {
+                if !used[j] {
+                    let reduced = costs[i0 - 1][j - 1] - u[i0] - v[j];
+
+                    if reduced < min[j] {
+                        min[j] = reduced;
+                        way[j] = j0;
+                    }
+
+                    if min[j] < delta {
+                        delta = min[j];
+                        j1 = j;
+                    }
+                }
+            }
+
+            for j in 0..=m {
+                if used[j] {
+                    u[matched[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min[j] -= delta;
+                }
+            }
+
+            j0 = j1;
+
+            if matched[j0] == 0 {
+                break;
+            }
+        }
+
+        // augment along the alternating path
+        while j0 != 0 {
+            let j1 = way[j0];
+
+            matched[j0] = matched[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut columns = vec![0; n];
+
+    for j in 1..=m {
+        if matched[j] != 0 {
+            columns[matched[j] - 1] = j - 1;
+        }
+    }
+
+    columns
+}
+
+/// Assigns the passenger clusters to the trains by a min-cost bipartite
+/// matching, each train picks up at most one cluster first. Trains without a
+/// feasible cluster are not assigned.
+pub fn assign(model: &Model) -> Vec<Assignment> {
+    let clusters = clusters(model);
+    let trains = model.trains.len();
+
+    if clusters.is_empty() || trains == 0 {
+        return vec![];
+    }
+
+    let costs: Vec<Vec<i64>> = (0..trains)
+        .map(|t_id| {
+            clusters
+                .iter()
+                .map(|cluster| cost(cluster, t_id, model))
+                .collect()
+        })
+        .collect();
+
+    // the smaller side is matched to the larger side
+    let pairs: Vec<(TId, usize)> = if trains <= clusters.len() {
+        hungarian(&costs).into_iter().enumerate().collect()
+    } else {
+        let transposed: Vec<Vec<i64>> = (0..clusters.len())
+            .map(|c| (0..trains).map(|t_id| costs[t_id][c]).collect())
+            .collect();
+
+        hungarian(&transposed)
+            .into_iter()
+            .enumerate()
+            .map(|(c, t_id)| (t_id, c))
+            .collect()
+    };
+
+    let mut assignments: Vec<Assignment> = pairs
+        .into_iter()
+        .filter(|&(t_id, c)| costs[t_id][c] < INFEASIBLE)
+        .map(|(t_id, c)| Assignment {
+            t_id,
+            passengers: load(&clusters[c], t_id, model),
+            pickup: clusters[c].start,
+        })
+        .collect();
+
+    // the most urgent clusters are picked up first
+    assignments
+        .sort_by_key(|assignment| -> Time { model.passengers[assignment.passengers[0]].arrival });
+
+    assignments
+}
+
+#[test]
+fn it_finds_the_min_cost_assignment() {
+    let costs = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+
+    assert_eq!(hungarian(&costs), vec![1, 0, 2]);
+}
+
+#[test]
+fn it_starts_wildcard_trains_at_their_pickup() {
+    use crate::planner::Planner;
+    use crate::train::Location as TLocation;
+
+    let model = crate::parser::parse(&include_str!("../test-cases/simple/input.txt").to_owned());
+    let assignments = assign(&model);
+
+    // both passenger clusters wait at S2
+    let wildcard = assignments
+        .iter()
+        .find(|assignment| assignment.t_id == 1)
+        .expect("The wildcard train is not assigned");
+
+    assert_eq!(wildcard.pickup, 1);
+
+    let mut planner = Planner::new(&model);
+
+    planner.pick_up(&assignments);
+
+    let solution = planner.solution();
+
+    assert!(solution.is_legal());
+    assert!(matches!(solution.0[0].t_location[1], TLocation::Station(1)));
+    assert!(solution
+        .0
+        .iter()
+        .any(|state| state.t_passengers[1].contains(&wildcard.passengers[0])));
+}
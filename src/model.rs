@@ -8,7 +8,9 @@ use crate::train::{Id as TId, Location as TLocation, Speed, StartStation, Train}
 use crate::types::{BuildHasher, Capacity, Time, TimeDiff};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
+use std::hash::{Hash, Hasher};
 
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 
 /// Holds a path between two stations and the total distance of the path.
@@ -185,6 +187,15 @@ impl Model {
         (self.connections[c_id].distance / self.trains[t_id].speed).ceil()
     }
 
+    /// The minimum number of ticks a train needs to travel between two
+    /// stations.
+    pub fn travel_time(&self, t_id: TId, a: SId, b: SId) -> Time {
+        self.distance(a, b)
+            .checked_div(self.trains[t_id].speed)
+            .and_then(|ticks| ticks.ceil().to_usize())
+            .unwrap_or(Time::MAX)
+    }
+
     /// Gets the destination station for the given start station id and the
     /// connection id.
     pub fn get_destination(&self, s: SId, c: CId) -> SId {
@@ -259,6 +270,16 @@ impl Model {
     }
 }
 
+impl Hash for Model {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.stations.hash(state);
+        self.connections.hash(state);
+        self.trains.hash(state);
+        self.passengers.hash(state);
+        self.used_trains.hash(state);
+    }
+}
+
 fn shortest_paths(stations: &Vec<Station>, connections: &Connections) -> Paths {
     let mut paths = Paths::default();
     let mut distances: Vec<Vec<Distance>> = (0..stations.len())
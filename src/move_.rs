@@ -1,7 +1,7 @@
 use crate::connection::Id as CId;
 use crate::model::Model;
 use crate::passenger::Id as PId;
-use crate::rule::Result;
+use crate::rule::{Result, Rule};
 use crate::state::State;
 use crate::station::Id as SId;
 use crate::train::Id as TId;
@@ -132,13 +132,44 @@ pub enum Move {
 impl Move {
     /// Determines whether a move is greater than another move.
     pub fn is_gt(&self, m: &Move, state: &State, model: &Model) -> bool {
-        for rule in &model.rules {
-            if let Result::Some(result) = rule.is_gt(self, m, state, model) {
-                return result;
-            }
-        }
+        self.is_gt_with(m, &model.rules, state, model)
+    }
+
+    /// Determines whether the move is greater than the given move by the
+    /// given rules instead of the rules of the model.
+    pub fn is_gt_with(&self, m: &Move, rules: &[Rule], state: &State, model: &Model) -> bool {
+        self.deciding_rule(m, rules, state, model)
+            .map_or(false, |(_, result)| result)
+    }
 
-        false
+    /// Gets the index of the first rule that decides whether the move is
+    /// greater than the given move, and its decision. `None` is returned when
+    /// no rule decides.
+    pub fn deciding_rule(
+        &self,
+        m: &Move,
+        rules: &[Rule],
+        state: &State,
+        model: &Model,
+    ) -> Option<(usize, bool)> {
+        rules
+            .iter()
+            .enumerate()
+            .find_map(|(i, rule)| match rule.is_gt(self, m, state, model) {
+                Result::Some(result) => Some((i, result)),
+                Result::None => Option::None,
+            })
+    }
+
+    /// Gets the id of the moved train, `None` is returned for no move.
+    pub fn t_id(&self) -> Option<TId> {
+        match self {
+            Move::Board(board) => Some(board.t_id),
+            Move::Detrain(detrain) => Some(detrain.t_id),
+            Move::Depart(depart) => Some(depart.t_id),
+            Move::Start(t_start) => Some(t_start.t_id),
+            Move::None(_) => Option::None,
+        }
     }
 
     /// Gets the string representation of the move for the given model.
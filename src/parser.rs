@@ -2,6 +2,7 @@ use crate::connection::{Connection, Connections, Distance};
 use crate::model::Model;
 use crate::passenger::Passenger;
 use crate::rules::get_rules;
+use crate::solution::Entry;
 use crate::station::Station;
 use crate::train::{Speed, StartStation, Train};
 use crate::types;
@@ -34,6 +35,82 @@ pub fn parse(string: &String) -> Model {
     Model::new(stations, connections, trains, passengers, get_rules())
 }
 
+/// Parses the entries of a timetable in the output format for each point in
+/// time, the timetable must belong to the given model.
+pub fn parse_timetable(string: &str, model: &Model) -> Result<Vec<Vec<Entry>>, String> {
+    let ids = |names: Vec<&String>| -> StationIds {
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id))
+            .collect()
+    };
+    let station_ids = ids(model.stations.iter().map(|s| &s.name).collect());
+    let connection_ids = ids(model.connections.iter().map(|c| &c.name).collect());
+    let train_ids = ids(model.trains.iter().map(|t| &t.name).collect());
+    let passenger_ids = ids(model.passengers.iter().map(|p| &p.name).collect());
+
+    let re_header = Regex::new(r"^\[(Train|Passenger):(.*)\]$").unwrap();
+    let mut timetable: Vec<Vec<Entry>> = vec![];
+    let mut header: Option<(String, types::Id)> = None;
+
+    for line in string
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+    {
+        let unknown = |name: &str| format!("Unknown entity \"{}\" in line \"{}\"!", name, line);
+
+        if let Some(captures) = re_header.captures(line) {
+            let ids = match &captures[1] {
+                "Train" => &train_ids,
+                _ => &passenger_ids,
+            };
+            let id = *ids.get(&captures[2]).ok_or_else(|| unknown(&captures[2]))?;
+
+            header = Some((captures[1].to_string(), id));
+            continue;
+        }
+
+        let attributes: Vec<&str> = line.split_whitespace().collect();
+        let t: usize = attributes[0]
+            .parse()
+            .map_err(|_| format!("Failed to read timetable line \"{}\"!", line))?;
+
+        let entry = match (&header, &attributes[1..]) {
+            (Some((kind, t_id)), ["Start", name]) if kind == "Train" => {
+                Entry::Start(*t_id, *station_ids.get(*name).ok_or_else(|| unknown(name))?)
+            }
+            (Some((kind, t_id)), ["Depart", name]) if kind == "Train" => Entry::Depart(
+                *t_id,
+                *connection_ids.get(*name).ok_or_else(|| unknown(name))?,
+            ),
+            (Some((kind, p_id)), ["Board", name]) if kind == "Passenger" => {
+                Entry::Board(*p_id, *train_ids.get(*name).ok_or_else(|| unknown(name))?)
+            }
+            (Some((kind, p_id)), ["Detrain"]) if kind == "Passenger" => Entry::Detrain(*p_id),
+            _ => return Err(format!("Failed to read timetable line \"{}\"!", line)),
+        };
+
+        if timetable.len() <= t {
+            timetable.resize(t + 1, vec![]);
+        }
+
+        timetable[t].push(entry);
+    }
+
+    // detrain passengers before boarding them to other trains
+    for entries in timetable.iter_mut() {
+        entries.sort_by_key(|entry| match entry {
+            Entry::Start(_, _) | Entry::Depart(_, _) => 0,
+            Entry::Detrain(_) => 1,
+            Entry::Board(_, _) => 2,
+        });
+    }
+
+    Ok(timetable)
+}
+
 fn filter(mut string: &str) -> String {
     let re_header = Regex::new(r"\[(.*?)\]").unwrap();
     let lines = string
@@ -194,3 +271,15 @@ P1 C B 10 3
 
     assert_eq!(model.stations.len(), 3);
 }
+
+#[test]
+fn it_parses_timetables() {
+    let model = parse(&include_str!("../test-cases/simple/input.txt").to_owned());
+    let timetable =
+        parse_timetable(include_str!("../test-cases/simple/output.txt"), &model).unwrap();
+    let solution = crate::solution::Solution::from_timetable(&model, &timetable);
+
+    assert_eq!(solution.0.len(), 7);
+    assert_eq!(solution.arrived_passengers().len(), 2);
+    assert_eq!(solution.fitness(), 3);
+}
@@ -14,7 +14,7 @@ pub enum Location {
     Station(SId),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
 pub struct Passenger {
     pub name: String,
     pub start: SId,
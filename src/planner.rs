@@ -0,0 +1,563 @@
+use crate::connection::Id as CId;
+use crate::matching::Assignment;
+use crate::model::Model;
+use crate::passenger::Id as PId;
+use crate::solution::{Entry, Solution};
+use crate::station::Id as SId;
+use crate::train::{Id as TId, StartStation};
+use crate::types::Time;
+use rust_decimal::prelude::ToPrimitive;
+
+/// The number of trains that are planned for each passenger, the trains are
+/// chosen by the estimated pickup time.
+const CANDIDATE_TRAINS: usize = 5;
+
+/// The end of the reservation of a train that stays at a station.
+const OPEN: Time = Time::MAX;
+
+/// A reservation table of a time-expanded graph: each station and connection
+/// holds the points in time it is occupied by a train.
+#[derive(Clone)]
+struct Reservations {
+    /// The inclusive intervals a train is located at a station. A train that
+    /// departs at `t` is still counted at `t`, as arrivals are applied before
+    /// the moves of a point in time.
+    stations: Vec<Vec<(Time, Time)>>,
+
+    /// The inclusive intervals a train is located on a connection, from the
+    /// departure until one tick before the arrival.
+    connections: Vec<Vec<(Time, Time)>>,
+}
+
+impl Reservations {
+    /// Determines whether a train can stay at the station from the given
+    /// point in time on, which guarantees that the train never blocks the
+    /// station of another reservation.
+    fn station_free(&self, s_id: SId, from: Time, model: &Model) -> bool {
+        max_load(&self.stations[s_id], from, OPEN) < model.stations[s_id].capacity.max(0) as usize
+    }
+
+    /// Determines whether a train can use the connection within the given
+    /// inclusive interval.
+    fn connection_free(&self, c_id: CId, from: Time, to: Time, model: &Model) -> bool {
+        max_load(&self.connections[c_id], from, to)
+            < model.connections[c_id].capacity.max(0) as usize
+    }
+
+    /// Removes one reservation of the station with the given interval.
+    fn release(&mut self, s_id: SId, interval: (Time, Time)) {
+        if let Some(i) = self.stations[s_id].iter().position(|&r| r == interval) {
+            self.stations[s_id].swap_remove(i);
+        }
+    }
+}
+
+/// Gets the maximum number of overlapping intervals within the given
+/// inclusive interval.
+fn max_load(intervals: &[(Time, Time)], from: Time, to: Time) -> usize {
+    let mut events: Vec<(Time, i32)> = intervals
+        .iter()
+        .filter(|&&(start, end)| start <= to && end >= from)
+        .flat_map(|&(start, end)| [(start.max(from), 1), (end.saturating_add(1), -1)])
+        .collect();
+
+    // ends are sorted before starts of the same point in time
+    events.sort_unstable();
+
+    let mut load = 0;
+    let mut max = 0;
+
+    for (_, delta) in events {
+        load += delta;
+        max = max.max(load);
+    }
+
+    max as usize
+}
+
+/// The position of a train during planning.
+#[derive(Clone, Copy)]
+struct Position {
+    /// The station the train is located at.
+    s_id: SId,
+
+    /// The point in time the train can move next.
+    free: Time,
+
+    /// The point in time the train has arrived at the station.
+    since: Time,
+}
+
+/// A departure of a planned trip.
+struct Hop {
+    c_id: CId,
+    to: SId,
+    depart: Time,
+    arrive: Time,
+}
+
+/// A planned trip of a train that picks up a passenger and brings it to the
+/// destination.
+struct Trip {
+    t_id: TId,
+    p_id: PId,
+    start: Option<SId>,
+    hops: Vec<Hop>,
+    board: Time,
+    arrival: Time,
+}
+
+/// A constructive heuristic that routes the trains through a time-expanded
+/// graph of stations and points in time.
+///
+/// Passengers are served one after another by the earliest arrival time, each
+/// passenger is assigned to the train that brings it to the destination the
+/// earliest. A train carries one passenger group at a time and stays at the
+/// last station of its trip, a station is only entered when it has space for
+/// the train from then on, which avoids the capacity deadlocks of the greedy
+/// rules.
+pub struct Planner<'a> {
+    model: &'a Model,
+    reservations: Reservations,
+    positions: Vec<Option<Position>>,
+    timetable: Vec<Vec<Entry>>,
+}
+
+impl<'a> Planner<'a> {
+    /// Constructs a new planner, the trains with start stations are placed.
+    pub fn new(model: &'a Model) -> Planner<'a> {
+        let mut reservations = Reservations {
+            stations: model.stations.iter().map(|_| vec![]).collect(),
+            connections: model.connections.iter().map(|_| vec![]).collect(),
+        };
+
+        let positions = model
+            .trains
+            .iter()
+            .map(|train| match train.start {
+                StartStation::Station(s_id) => {
+                    reservations.stations[s_id].push((0, OPEN));
+
+                    Some(Position {
+                        s_id,
+                        free: 1,
+                        since: 0,
+                    })
+                }
+                StartStation::Any => None,
+            })
+            .collect();
+
+        Planner {
+            model,
+            reservations,
+            positions,
+            timetable: vec![vec![]],
+        }
+    }
+
+    /// Plans the trips of all passengers and returns the solution.
+    pub fn plan(mut self) -> Solution {
+        let mut passengers: Vec<PId> = (0..self.model.passengers.len()).collect();
+
+        passengers.sort_by_key(|&p_id| self.model.passengers[p_id].arrival);
+
+        for p_id in passengers {
+            if let Some(trip) = self
+                .best_trip(p_id, CANDIDATE_TRAINS)
+                .or_else(|| self.clear_and_retry(p_id))
+            {
+                self.commit(trip);
+            }
+        }
+
+        self.solution()
+    }
+
+    /// Lets each assigned train pick up its passengers without bringing them
+    /// to the destination, e.g. to start a search from the assignment.
+    pub fn pick_up(&mut self, assignments: &[Assignment]) {
+        let model = self.model;
+
+        for assignment in assignments {
+            let t_id = assignment.t_id;
+            let (position, start) = match self.positions[t_id] {
+                Some(position) => (position, None),
+                None => match self.start_station(assignment.pickup) {
+                    Some(s_id) => (
+                        Position {
+                            s_id,
+                            free: 1,
+                            since: 0,
+                        },
+                        Some(s_id),
+                    ),
+                    None => continue,
+                },
+            };
+
+            if start.is_none() {
+                self.reservations
+                    .release(position.s_id, (position.since, OPEN));
+            }
+
+            let mut hops = vec![];
+            let arrival = self.route(
+                t_id,
+                position.s_id,
+                assignment.pickup,
+                position.free,
+                &mut hops,
+            );
+
+            if start.is_none() {
+                self.reservations.stations[position.s_id].push((position.since, OPEN));
+            }
+
+            // the groups board one after another
+            let board = match arrival {
+                Some(board) if board + assignment.passengers.len() <= model.t_max => board,
+                _ => continue,
+            };
+
+            let (s_id, since) = self.travel(t_id, start, &hops);
+
+            for (i, &p_id) in assignment.passengers.iter().enumerate() {
+                self.entry(board + i, Entry::Board(p_id, t_id));
+            }
+
+            self.positions[t_id] = Some(Position {
+                s_id,
+                free: board + assignment.passengers.len(),
+                since,
+            });
+        }
+    }
+
+    /// Gets the solution of the planned timetable.
+    pub fn solution(mut self) -> Solution {
+        // starts, departures and detrains precede the boardings of a point in
+        // time, like in parsed timetables
+        for entries in self.timetable.iter_mut() {
+            entries.sort_by_key(|entry| match entry {
+                Entry::Start(..) | Entry::Depart(..) => 0,
+                Entry::Detrain(..) => 1,
+                Entry::Board(..) => 2,
+            });
+        }
+
+        Solution::from_timetable(self.model, &self.timetable)
+    }
+
+    /// Gets the trip of the train that brings the passenger to the
+    /// destination the earliest, out of the given number of trains with the
+    /// earliest estimated pickup.
+    fn best_trip(&mut self, p_id: PId, candidate_trains: usize) -> Option<Trip> {
+        let model = self.model;
+        let passenger = &model.passengers[p_id];
+
+        let mut candidates: Vec<(Time, TId)> = model
+            .trains
+            .iter()
+            .enumerate()
+            .filter(|(_, train)| train.capacity >= passenger.size)
+            .map(|(t_id, _)| match self.positions[t_id] {
+                Some(position) => (
+                    model
+                        .travel_time(t_id, position.s_id, passenger.start)
+                        .saturating_add(position.free),
+                    t_id,
+                ),
+                None => (1, t_id),
+            })
+            .collect();
+
+        candidates.sort_unstable();
+        candidates.truncate(candidate_trains);
+
+        candidates
+            .into_iter()
+            .filter_map(|(_, t_id)| self.trip(t_id, p_id))
+            .min_by_key(|trip| trip.arrival)
+    }
+
+    /// Moves the trains that are parked on the shortest path of the passenger
+    /// aside and plans the passenger again with all trains, as the stations
+    /// freed by the parked trains may open routes for trains that are not
+    /// among the closest ones. The parked trains are kept when the passenger
+    /// still cannot be planned.
+    fn clear_and_retry(&mut self, p_id: PId) -> Option<Trip> {
+        let model = self.model;
+        let passenger = &model.passengers[p_id];
+        let path = &model
+            .paths
+            .get(&(passenger.start, passenger.destination))?
+            .path;
+
+        let reservations = self.reservations.clone();
+        let positions = self.positions.clone();
+        let timetable = self.timetable.clone();
+
+        for t_id in 0..model.trains.len() {
+            if let Some(position) = self.positions[t_id] {
+                if path.contains(&position.s_id) {
+                    self.relocate(t_id, path);
+                }
+            }
+        }
+
+        let trip = self.best_trip(p_id, model.trains.len());
+
+        if trip.is_none() {
+            self.reservations = reservations;
+            self.positions = positions;
+            self.timetable = timetable;
+        }
+
+        trip
+    }
+
+    /// Moves the parked train to the closest station that is not on the given
+    /// path and has space for the train.
+    fn relocate(&mut self, t_id: TId, path: &[SId]) {
+        let model = self.model;
+        let position = match self.positions[t_id] {
+            Some(position) => position,
+            None => return,
+        };
+
+        self.reservations
+            .release(position.s_id, (position.since, OPEN));
+
+        let mut targets: Vec<SId> = (0..model.stations.len())
+            .filter(|s_id| !path.contains(s_id))
+            .collect();
+
+        targets.sort_by_key(|&s_id| model.distance(position.s_id, s_id));
+
+        let hops = targets.into_iter().find_map(|target| {
+            let mut hops = vec![];
+
+            self.route(t_id, position.s_id, target, position.free, &mut hops)?;
+            Some(hops)
+        });
+
+        self.reservations.stations[position.s_id].push((position.since, OPEN));
+
+        if let Some(hops) = hops {
+            let (s_id, since) = self.travel(t_id, None, &hops);
+
+            self.positions[t_id] = Some(Position {
+                s_id,
+                free: since.max(position.free),
+                since,
+            });
+        }
+    }
+
+    /// Gets the free station that is closest to the given station, where a
+    /// train without a position is started.
+    fn start_station(&self, near: SId) -> Option<SId> {
+        let model = self.model;
+
+        (0..model.stations.len())
+            .filter(|&s_id| self.reservations.station_free(s_id, 0, model))
+            .min_by_key(|&s_id| model.distance(s_id, near))
+    }
+
+    /// Plans the trip of the given train for the given passenger.
+    fn trip(&mut self, t_id: TId, p_id: PId) -> Option<Trip> {
+        let model = self.model;
+        let passenger = &model.passengers[p_id];
+
+        let (position, start) = match self.positions[t_id] {
+            Some(position) => (position, None),
+            None => {
+                let s_id = self.start_station(passenger.start)?;
+
+                let position = Position {
+                    s_id,
+                    free: 1,
+                    since: 0,
+                };
+
+                (position, Some(s_id))
+            }
+        };
+
+        // the reservation of the train at its station is replaced by the trip
+        if start.is_none() {
+            self.reservations
+                .release(position.s_id, (position.since, OPEN));
+        }
+
+        let mut hops = vec![];
+        let pickup = self.route(
+            t_id,
+            position.s_id,
+            passenger.start,
+            position.free,
+            &mut hops,
+        );
+        let trip = pickup.and_then(|board| {
+            let arrival = self.route(
+                t_id,
+                passenger.start,
+                passenger.destination,
+                board + 1,
+                &mut hops,
+            )?;
+
+            Some(Trip {
+                t_id,
+                p_id,
+                start,
+                hops,
+                board,
+                arrival,
+            })
+        });
+
+        if start.is_none() {
+            self.reservations.stations[position.s_id].push((position.since, OPEN));
+        }
+
+        trip.filter(|trip| trip.arrival <= model.t_max)
+    }
+
+    /// Routes the train along the shortest path between the given stations,
+    /// the train departs at `t` at the earliest. The point in time the train
+    /// arrives is returned.
+    fn route(
+        &self,
+        t_id: TId,
+        from: SId,
+        to: SId,
+        mut t: Time,
+        hops: &mut Vec<Hop>,
+    ) -> Option<Time> {
+        let model = self.model;
+        let path = &model.paths.get(&(from, to))?.path;
+
+        // unreachable stations have no complete path
+        if from != to && (path.first() != Some(&from) || path.last() != Some(&to)) {
+            return None;
+        }
+
+        for stations in path.windows(2) {
+            let (a, b) = (stations[0], stations[1]);
+            let connections: Vec<(CId, Time)> = model.station_connections[a]
+                .iter()
+                .filter(|&&c_id| model.get_destination(a, c_id) == b)
+                .map(|&c_id| {
+                    let ticks = model.train_arrival(t_id, c_id).to_usize().unwrap_or(OPEN);
+
+                    (c_id, ticks.max(1))
+                })
+                .collect();
+
+            // the earliest departure via any of the connections
+            let hop = (t..=model.t_max).find_map(|depart| {
+                connections.iter().find_map(|&(c_id, ticks)| {
+                    let arrive = depart.saturating_add(ticks);
+
+                    if arrive <= model.t_max
+                        && self
+                            .reservations
+                            .connection_free(c_id, depart, arrive - 1, model)
+                        && self.reservations.station_free(b, arrive, model)
+                    {
+                        Some(Hop {
+                            c_id,
+                            to: b,
+                            depart,
+                            arrive,
+                        })
+                    } else {
+                        None
+                    }
+                })
+            })?;
+
+            t = hop.arrive;
+            hops.push(hop);
+        }
+
+        Some(t)
+    }
+
+    /// Adds the trip to the reservations and the timetable.
+    fn commit(&mut self, trip: Trip) {
+        let (s_id, since) = self.travel(trip.t_id, trip.start, &trip.hops);
+
+        self.entry(trip.board, Entry::Board(trip.p_id, trip.t_id));
+        self.entry(trip.arrival.max(trip.board + 1), Entry::Detrain(trip.p_id));
+
+        self.positions[trip.t_id] = Some(Position {
+            s_id,
+            free: trip.arrival.max(trip.board + 1) + 1,
+            since,
+        });
+    }
+
+    /// Adds the departures of the train to the reservations and the
+    /// timetable, the station and the point in time of the last arrival are
+    /// returned.
+    fn travel(&mut self, t_id: TId, start: Option<SId>, hops: &[Hop]) -> (SId, Time) {
+        let (mut s_id, mut since) = match (start, self.positions[t_id]) {
+            (Some(s_id), _) => {
+                self.entry(0, Entry::Start(t_id, s_id));
+                (s_id, 0)
+            }
+            (None, Some(position)) => {
+                self.reservations
+                    .release(position.s_id, (position.since, OPEN));
+                (position.s_id, position.since)
+            }
+            (None, None) => unreachable!("The train has neither a start nor a position"),
+        };
+
+        for hop in hops {
+            self.reservations.stations[s_id].push((since, hop.depart));
+            self.reservations.connections[hop.c_id].push((hop.depart, hop.arrive - 1));
+            self.entry(hop.depart, Entry::Depart(t_id, hop.c_id));
+
+            s_id = hop.to;
+            since = hop.arrive;
+        }
+
+        self.reservations.stations[s_id].push((since, OPEN));
+
+        (s_id, since)
+    }
+
+    /// Adds the entry to the timetable at the given point in time.
+    fn entry(&mut self, t: Time, entry: Entry) {
+        if self.timetable.len() <= t {
+            self.timetable.resize(t + 1, vec![]);
+        }
+
+        self.timetable[t].push(entry);
+    }
+}
+
+#[test]
+fn it_moves_parked_trains_out_of_the_way() {
+    let model =
+        crate::parser::parse(&include_str!("../test-cases/stationCapacity/input.txt").to_owned());
+    let solution = Planner::new(&model).plan();
+
+    assert_eq!(solution.arrived_passengers().len(), 1);
+    assert_eq!(solution.fitness(), 0);
+}
+
+#[test]
+fn it_plans_passengers_behind_parked_trains() {
+    let model = crate::parser::parse(
+        &include_str!("../test-cases/unusedWildcardTrain/input.txt").to_owned(),
+    );
+    let solution = Planner::new(&model).plan();
+
+    assert_eq!(solution.arrived_passengers().len(), model.passengers.len());
+    assert_eq!(solution.fitness(), 0);
+}
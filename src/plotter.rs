@@ -1,4 +1,4 @@
-use crate::tabu::TabuGeneticSearch;
+use crate::search::SearchReport;
 use crate::types::TimeDiff;
 use plotters::prelude::*;
 use std::path::Path;
@@ -24,19 +24,19 @@ impl Plotter {
     }
 
     /// Plofts the fitness progress of a tabu-enhances genetic search procedure.
-    pub fn plot_fitness(&self, tabu: &TabuGeneticSearch) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn plot_fitness(&self, report: &SearchReport) -> Result<(), Box<dyn std::error::Error>> {
         let file_name = self.file_name("fitness");
         let root = BitMapBackend::new(&file_name, (1024, 800)).into_drawing_area();
 
         root.fill(&WHITE)?;
 
-        let min = tabu
+        let min = report
             .fitness
             .iter()
             .fold(TimeDiff::MAX, |a, &b| a.min(b))
             .min(-0);
 
-        let max = tabu.fitness.iter().fold(0, |a, &b| match a > b {
+        let max = report.fitness.iter().fold(0, |a, &b| match a > b {
             true => a,
             false => b,
         });
@@ -45,13 +45,13 @@ impl Plotter {
             .set_label_area_size(LabelAreaPosition::Left, 50)
             .set_label_area_size(LabelAreaPosition::Bottom, 50)
             .caption("Fitness By Iteration", ("sans-serif", 30))
-            .build_cartesian_2d(-10..(tabu.fitness.len() as i32), (min - 2)..(max + 2))
+            .build_cartesian_2d(-10..(report.fitness.len() as i32), (min - 2)..(max + 2))
             .unwrap();
 
         chart.configure_mesh().draw().unwrap();
 
         chart
-            .draw_series(tabu.fitness.iter().enumerate().map(|(x, y)| {
+            .draw_series(report.fitness.iter().enumerate().map(|(x, y)| {
                 Circle::new((x as i32, *y), 1, Into::<ShapeStyle>::into(&BLACK).filled())
             }))
             .unwrap();
@@ -0,0 +1,309 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::rules::{
+    get_rules_by_names, parse_rule_set_names, requires_deadlocks, DEFAULT_RULE_SETS,
+};
+use crate::search::{Objective, SearchConfig};
+use crate::tabu::TabuGeneticSearch;
+use crate::train::StartStation;
+use crate::types::TimeDiff;
+use std::fmt;
+use std::thread;
+
+/// Cheap features of a model that characterize its instance family.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Features {
+    /// The number of connections relative to the number of station pairs.
+    pub density: f64,
+
+    /// The fraction of trains without a start station.
+    pub wildcards: f64,
+
+    /// The sum of the group sizes relative to the sum of the train
+    /// capacities.
+    pub tightness: f64,
+
+    /// The fraction of stations that are the start or the destination of a
+    /// passenger group.
+    pub spread: f64,
+}
+
+impl Features {
+    /// Extracts the features of the model.
+    pub fn of(model: &Model) -> Features {
+        let stations = model.stations.len() as f64;
+        let pairs = stations * (stations - 1.0) / 2.0;
+        let wildcards = model
+            .trains
+            .iter()
+            .filter(|train| matches!(train.start, StartStation::Any))
+            .count();
+        let sizes: f64 = model.passengers.iter().map(|p| p.size as f64).sum();
+        let capacities: f64 = model.trains.iter().map(|t| t.capacity as f64).sum();
+        let mut demand = vec![false; model.stations.len()];
+
+        for passenger in &model.passengers {
+            demand[passenger.start] = true;
+            demand[passenger.destination] = true;
+        }
+
+        Features {
+            density: match pairs > 0.0 {
+                true => model.connections.len() as f64 / pairs,
+                false => 0.0,
+            },
+            wildcards: wildcards as f64 / model.trains.len().max(1) as f64,
+            tightness: match capacities > 0.0 {
+                true => sizes / capacities,
+                false => 0.0,
+            },
+            spread: demand.iter().filter(|&&d| d).count() as f64 / stations.max(1.0),
+        }
+    }
+
+    /// Gets the euclidean distance to the given features.
+    pub fn distance(&self, other: &Features) -> f64 {
+        ((self.density - other.density).powi(2)
+            + (self.wildcards - other.wildcards).powi(2)
+            + (self.tightness - other.tightness).powi(2)
+            + (self.spread - other.spread).powi(2))
+        .sqrt()
+    }
+}
+
+impl fmt::Display for Features {
+    /// Formats the features like the header of a portfolio entry.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "[density={:.2} wildcards={:.2} tightness={:.2} spread={:.2}]",
+            self.density, self.wildcards, self.tightness, self.spread
+        )
+    }
+}
+
+/// A rule configuration and the features of the instances it is meant for.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub features: Features,
+    pub rule_sets: Vec<String>,
+}
+
+/// Rule configurations for different instance families.
+#[derive(Clone, Debug, Default)]
+pub struct Portfolio(pub Vec<Entry>);
+
+impl Portfolio {
+    /// Parses a portfolio, each entry starts with its features in brackets
+    /// followed by its rule sets like in a rules file, e.g.
+    ///
+    /// ```text
+    /// [density=0.1 wildcards=0.5 tightness=0.8 spread=0.9]
+    /// avoid_station_overload
+    /// detrain_arrived_passenger
+    /// ```
+    ///
+    /// Missing features are `0`, the rule sets are validated and each entry
+    /// needs at least one rule set.
+    pub fn parse(string: &str) -> Result<Portfolio, String> {
+        let mut entries: Vec<Entry> = vec![];
+        let mut rule_sets = String::new();
+
+        for line in string.lines().chain(std::iter::once("[]")) {
+            let line = line.trim();
+
+            if !(line.starts_with('[') && line.ends_with(']')) {
+                rule_sets.push_str(line);
+                rule_sets.push('\n');
+                continue;
+            }
+
+            let names = parse_rule_set_names(&rule_sets);
+
+            match entries.last_mut() {
+                Some(entry) if names.is_empty() => {
+                    return Err(format!("No rule sets for {}!", entry.features));
+                }
+                Some(entry) => {
+                    get_rules_by_names(&names)?;
+                    entry.rule_sets = names.into_iter().map(|name| name.to_owned()).collect();
+                }
+                None if !names.is_empty() => {
+                    return Err("Rule sets without features!".to_owned());
+                }
+                None => (),
+            }
+
+            rule_sets.clear();
+
+            let features = &line[1..line.len() - 1];
+
+            if features.trim().is_empty() {
+                continue;
+            }
+
+            let mut entry = Entry {
+                features: Features::default(),
+                rule_sets: vec![],
+            };
+
+            for feature in features.split_whitespace() {
+                let (name, value) = feature
+                    .split_once('=')
+                    .ok_or(format!("Invalid feature \"{}\"!", feature))?;
+                let value: f64 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid feature \"{}\"!", feature))?;
+
+                match name {
+                    "density" => entry.features.density = value,
+                    "wildcards" => entry.features.wildcards = value,
+                    "tightness" => entry.features.tightness = value,
+                    "spread" => entry.features.spread = value,
+                    _ => return Err(format!("Unknown feature \"{}\"!", name)),
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(Portfolio(entries))
+    }
+
+    /// Gets the rule sets of the entry with the closest features, the default
+    /// rule sets are used for an empty portfolio.
+    pub fn select(&self, features: &Features) -> Vec<String> {
+        self.0
+            .iter()
+            .min_by(|a, b| {
+                a.features
+                    .distance(features)
+                    .total_cmp(&b.features.distance(features))
+            })
+            .map(|entry| entry.rule_sets.clone())
+            .unwrap_or(
+                DEFAULT_RULE_SETS
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            )
+    }
+}
+
+/// The result of a rule configuration in a race.
+pub struct Run {
+    pub rule_sets: Vec<String>,
+    pub fitness: TimeDiff,
+
+    /// The moves of the best solution, which can be replayed to continue the
+    /// search.
+    pub moves: Vec<Vec<Move>>,
+}
+
+/// Races the rule configurations in parallel, each search runs for the given
+/// number of milli seconds. The rules are not thread safe, hence each thread
+/// constructs its own model. At most as many searches as the available
+/// parallelism run at the same time. The runs are returned by their fitness
+/// for the objective, the best run comes first.
+pub fn race(
+    model: impl Fn() -> Model + Sync,
+    candidates: &[Vec<String>],
+    max_millis: u128,
+    seed: Option<u64>,
+    objective: Objective,
+) -> Vec<Run> {
+    let model = &model;
+    let parallelism = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut runs: Vec<Run> = vec![];
+
+    for chunk in candidates.chunks(parallelism) {
+        thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|rule_sets| {
+                    scope.spawn(move || {
+                        let model = model();
+                        let mut config = SearchConfig::default()
+                            .time(max_millis)
+                            .objective(objective)
+                            .rules(get_rules_by_names(rule_sets).expect("Unknown rule set"))
+                            .detect_deadlocks(requires_deadlocks(rule_sets));
+
+                        if let Some(seed) = seed {
+                            config = config.seed(seed);
+                        }
+
+                        let report = TabuGeneticSearch::new(config).search(&model);
+
+                        Run {
+                            rule_sets: rule_sets.clone(),
+                            fitness: objective.fitness(&report.solution),
+                            moves: report.solution.moves(),
+                        }
+                    })
+                })
+                .collect();
+
+            runs.extend(
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("Race failed")),
+            );
+        });
+    }
+
+    runs.sort_by_key(|run| run.fitness);
+    runs
+}
+
+#[test]
+fn it_selects_the_closest_entry() {
+    let portfolio = Portfolio::parse(
+        "
+[density=0.1 wildcards=0.0]
+avoid_station_overload
+[density=0.1 wildcards=1.0]
+# comment
+detrain_arrived_passenger, choose_train_starts
+",
+    )
+    .unwrap();
+    let features = Features {
+        wildcards: 0.8,
+        ..Features::default()
+    };
+
+    assert_eq!(
+        portfolio.select(&features),
+        vec!["detrain_arrived_passenger", "choose_train_starts"]
+    );
+    assert!(Portfolio::parse("[density=0.1]\nunknown").is_err());
+    assert!(Portfolio::parse("[density=0.1]\n[density=0.2]\nfree_up_space").is_err());
+    assert!(Portfolio::parse("[density=0.1]\n# comment\n").is_err());
+}
+
+#[test]
+fn it_ranks_the_runs_by_the_objective() {
+    let input = include_str!("../test-cases/simple/input.txt").to_owned();
+    let candidates: Vec<Vec<String>> = [&DEFAULT_RULE_SETS[..], &DEFAULT_RULE_SETS[..3]]
+        .iter()
+        .map(|names| names.iter().map(|name| name.to_string()).collect())
+        .collect();
+    let runs = race(
+        || crate::parser::parse(&input),
+        &candidates,
+        50,
+        Some(1),
+        Objective::MaxDelay,
+    );
+    let model = crate::parser::parse(&input);
+
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].fitness <= runs[1].fitness);
+
+    for run in &runs {
+        let solution = crate::solution::Solution::replay(&model, &run.moves);
+
+        assert_eq!(run.fitness, solution.max_delay());
+    }
+}
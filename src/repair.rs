@@ -0,0 +1,239 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::solution::Solution;
+use crate::state::State;
+use crate::station::Id as SId;
+use crate::train::Location as TLocation;
+use fxhash::hash64;
+
+/// A solution whose overloaded station has been repaired.
+pub struct Repair {
+    /// The repaired solution.
+    pub solution: Solution,
+
+    /// The state of the point in time after the solution.
+    pub state: State,
+
+    /// The hash of the state that has led to the overloaded station, which
+    /// should be made tabu.
+    pub tabu: u64,
+}
+
+/// Repairs the given illegal state that follows the solution. An empty train
+/// that waits at the overloaded station leaves it for a neighbouring station
+/// with free capacity, right before the other trains arrive. Otherwise the
+/// train that has departed last of all trains that arrived at the overloaded
+/// station does not depart, so it waits at its station instead.
+///
+/// `None` is returned when neither a train can leave nor an arrival is
+/// responsible for the overloaded station.
+pub fn repair(solution: &Solution, state: &State, model: &Model) -> Option<Repair> {
+    let s_id = (0..model.stations.len()).find(|&s_id| state.s_capacity[s_id] < 0)?;
+
+    leave(solution, s_id, model).or_else(|| wait(solution, state, s_id, model))
+}
+
+/// Lets an empty train that has not moved at the last point in time of the
+/// solution depart from the overloaded station, the neighbouring station with
+/// the most free capacity is preferred. The arriving trains keep their moves,
+/// a station that is overloaded by several trains is repaired again.
+fn leave(solution: &Solution, s_id: SId, model: &Model) -> Option<Repair> {
+    let previous = solution.0.last()?;
+
+    let depart = (0..model.trains.len())
+        .filter(|&t_id| {
+            matches!(previous.t_location[t_id], TLocation::Station(at) if at == s_id)
+                && previous.t_passengers[t_id].is_empty()
+                && previous.train_move(t_id).is_none()
+        })
+        .flat_map(|t_id| previous.get_moves(t_id, model))
+        .filter(|m| match m {
+            Move::Depart(depart) => previous.s_capacity[depart.to] > 0,
+            _ => false,
+        })
+        .filter(|m| previous.is_legal_move(m, model))
+        .min_by_key(|m| match m {
+            Move::Depart(depart) => (-previous.s_capacity[depart.to], depart.t_id),
+            _ => (0, 0),
+        })?;
+
+    let mut repaired = Solution(solution.0.clone());
+    let last = repaired.0.last_mut()?;
+
+    last.push(depart, model);
+
+    let mut state = last.clone();
+
+    state.next(model);
+
+    Some(Repair {
+        solution: repaired,
+        state,
+        tabu: hash64(previous),
+    })
+}
+
+/// Lets the train that has departed last of all trains that arrived at the
+/// overloaded station wait at its station instead.
+///
+/// The moves after the departure are replayed as long as they are legal, the
+/// search continues after the replayed moves.
+fn wait(solution: &Solution, state: &State, s_id: SId, model: &Model) -> Option<Repair> {
+    let previous = solution.0.last()?;
+
+    // the arrival with the latest departure
+    let (t_depart, t_id) = (0..model.trains.len())
+        .filter_map(
+            |t_id| match (previous.t_location[t_id], state.t_location[t_id]) {
+                (TLocation::Connection(_, to, t_start), TLocation::Station(at))
+                    if to == s_id && at == s_id =>
+                {
+                    Some((t_start, t_id))
+                }
+                _ => None,
+            },
+        )
+        .max()?;
+
+    let depart = *solution
+        .0
+        .get(t_depart)?
+        .moves
+        .iter()
+        .find(|m| matches!(m, Move::Depart(depart) if depart.t_id == t_id))?;
+
+    // the state right after the departure, like it is hashed by the search
+    let mut departed = solution.0[t_depart].clone();
+
+    while let Some(m) = departed.pop(model) {
+        if m == depart {
+            departed.push(m, model);
+            break;
+        }
+    }
+
+    let tabu = hash64(&departed);
+
+    let mut repaired = Solution(solution.0[..t_depart].to_vec());
+    let mut state = match t_depart {
+        0 => model.initial_state(),
+        _ => {
+            let mut state = solution.0[t_depart - 1].clone();
+
+            state.next(model);
+            state
+        }
+    };
+
+    'replay: for t in t_depart..solution.0.len() {
+        if t > t_depart {
+            state.next(model);
+
+            if !state.is_legal() {
+                break;
+            }
+        }
+
+        let mut next = state.clone();
+
+        for m in &solution.0[t].moves {
+            if *m == depart {
+                continue;
+            }
+
+            if !next.is_legal_move(m, model) {
+                break 'replay;
+            }
+
+            next.push(*m, model);
+        }
+
+        repaired.0.push(next.clone());
+        state = next;
+    }
+
+    // the search continues at the first point in time that is not replayed
+    let mut state = repaired.0.last()?.clone();
+
+    state.next(model);
+
+    Some(Repair {
+        solution: repaired,
+        state,
+        tabu,
+    })
+}
+
+#[test]
+fn it_lets_the_last_departed_train_wait() {
+    use crate::solution::Entry;
+
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 1
+S2 1
+[Lines]
+L1 S1 S2 1 1
+[Trains]
+T1 S1 2 5
+T2 S2 1 5
+[Passengers]
+P1 S1 S2 5 10
+"
+        .to_owned(),
+    );
+
+    // T1 arrives at S2 at 2, where T2 is located, which cannot leave as T1
+    // uses the only line
+    let solution = Solution::from_timetable(&model, &[vec![], vec![Entry::Depart(0, 0)]]);
+    let mut state = solution.0[1].clone();
+
+    state.next(&model);
+    assert!(!state.is_legal());
+
+    let repaired = repair(&solution, &state, &model).unwrap();
+
+    assert!(repaired.state.is_legal());
+    assert!(repaired.state.t_location[0] == TLocation::Station(0));
+    assert_eq!(repaired.solution.0.len(), 2);
+}
+
+#[test]
+fn it_lets_the_blocking_train_leave() {
+    use crate::solution::Entry;
+
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 1
+S2 1
+S3 1
+[Lines]
+L1 S1 S2 1 1
+L2 S2 S3 1 1
+[Trains]
+T1 S1 2 5
+T2 S2 1 5
+[Passengers]
+P1 S1 S2 5 10
+"
+        .to_owned(),
+    );
+
+    // T1 arrives at S2 at 2, where the empty T2 waits
+    let solution = Solution::from_timetable(&model, &[vec![], vec![Entry::Depart(0, 0)]]);
+    let mut state = solution.0[1].clone();
+
+    state.next(&model);
+    assert!(!state.is_legal());
+
+    let repaired = repair(&solution, &state, &model).unwrap();
+
+    // T2 leaves for S3 at 1 and arrives at 2 like T1, which keeps its
+    // departure
+    assert!(repaired.state.is_legal());
+    assert!(repaired.state.t_location[0] == TLocation::Station(1));
+    assert!(repaired.state.t_location[1] == TLocation::Station(2));
+    assert_eq!(repaired.solution.0.len(), 2);
+}
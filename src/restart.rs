@@ -0,0 +1,139 @@
+use crate::bound::min_journey_time;
+use crate::model::Model;
+use crate::solution::Solution;
+use rand::Rng;
+use std::str::FromStr;
+
+/// Strategies to choose the point in time the genetic search is restarted
+/// from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Strategy {
+    /// A uniformly distributed point in time.
+    Uniform,
+    /// The latest point in time the first delayed passenger could still have
+    /// been brought to the destination in time.
+    FirstDelay,
+    /// A point in time just before a station or a connection has been fully
+    /// occupied.
+    CapacityConflict,
+    /// A decaying distribution that favours late points in time.
+    LateBiased,
+    /// A random choice of all other strategies.
+    Mixed,
+}
+
+/// All strategies that choose a point in time on their own.
+pub const STRATEGIES: [Strategy; 4] = [
+    Strategy::Uniform,
+    Strategy::FirstDelay,
+    Strategy::CapacityConflict,
+    Strategy::LateBiased,
+];
+
+impl Strategy {
+    /// Gets the name of the strategy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Uniform => "uniform",
+            Strategy::FirstDelay => "first-delay",
+            Strategy::CapacityConflict => "capacity-conflict",
+            Strategy::LateBiased => "late-biased",
+            Strategy::Mixed => "mixed",
+        }
+    }
+
+    /// Chooses the point in time the search is restarted from, the strategy
+    /// that has chosen the point in time is returned as well.
+    pub fn restart_point<R: Rng>(
+        &self,
+        solution: &Solution,
+        model: &Model,
+        rnd: &mut R,
+    ) -> (Strategy, usize) {
+        let len = solution.0.len();
+
+        let t = match self {
+            Strategy::Uniform => Some(rnd.gen_range(0..len)),
+            Strategy::FirstDelay => first_delay(solution, model),
+            Strategy::CapacityConflict => {
+                let conflicts = capacity_conflicts(solution);
+
+                match conflicts.is_empty() {
+                    true => None,
+                    false => Some(conflicts[rnd.gen_range(0..conflicts.len())] - 1),
+                }
+            }
+            Strategy::LateBiased => {
+                // exponentially distributed distance to the latest point in time
+                let distance = -rnd.gen::<f64>().ln() * (len as f64 / 8.0);
+
+                Some(len - 1 - (distance as usize).min(len - 1))
+            }
+            Strategy::Mixed => {
+                return STRATEGIES[rnd.gen_range(0..STRATEGIES.len())]
+                    .restart_point(solution, model, rnd);
+            }
+        };
+
+        match t {
+            Some(t) => (*self, t.min(len - 1)),
+            // fall back to a uniformly distributed point in time
+            None => Strategy::Uniform.restart_point(solution, model, rnd),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        STRATEGIES
+            .iter()
+            .chain([Strategy::Mixed].iter())
+            .find(|strategy| strategy.name() == string)
+            .copied()
+            .ok_or_else(|| format!("Unknown restart strategy \"{}\"!", string))
+    }
+}
+
+/// Counts how often a strategy has been used and how often a restart led to
+/// an improvement of the best solution.
+#[derive(Clone, Default)]
+pub struct Statistics {
+    /// The number of restarts.
+    pub restarts: usize,
+
+    /// The number of restarts that led to an improvement.
+    pub improvements: usize,
+}
+
+/// Gets the point in time the delayed passenger with the earliest arrival
+/// time must be boarded at the latest.
+fn first_delay(solution: &Solution, model: &Model) -> Option<usize> {
+    let delays = solution.delays();
+
+    (0..model.passengers.len())
+        .filter(|&p_id| delays[p_id] > 0)
+        .min_by_key(|&p_id| model.passengers[p_id].arrival)
+        .and_then(|p_id| {
+            let journey = min_journey_time(model, p_id)?;
+
+            Some(model.passengers[p_id].arrival.saturating_sub(journey))
+        })
+}
+
+/// Gets the points in time a station or a connection has been fully occupied.
+fn capacity_conflicts(solution: &Solution) -> Vec<usize> {
+    (1..solution.0.len())
+        .filter(|&t| {
+            let (previous, state) = (&solution.0[t - 1], &solution.0[t]);
+
+            state
+                .s_capacity
+                .iter()
+                .zip(previous.s_capacity.iter())
+                .chain(state.c_capacity.iter().zip(previous.c_capacity.iter()))
+                .any(|(&capacity, &previous)| capacity <= 0 && previous > 0)
+        })
+        .collect()
+}
@@ -0,0 +1,210 @@
+use crate::model::Model;
+use crate::move_::{Move, None};
+use crate::passenger::Location as PLocation;
+use crate::rule::Rule;
+use crate::state::State;
+use crate::train::{Id as TId, Location as TLocation};
+use crate::types::{Time, TimeDiff};
+use rust_decimal::prelude::ToPrimitive;
+
+/// Lookahead for the move of a train: the best candidate moves by the rules
+/// are continued greedily for some points in time and the move with the
+/// lowest estimated delay is chosen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rollout {
+    /// The number of candidate moves that are rolled out.
+    pub width: usize,
+
+    /// The number of points in time each candidate is continued.
+    pub depth: usize,
+}
+
+impl Rollout {
+    /// Chooses the move of the given train from the candidates, which are
+    /// ordered by the rules, the best candidate comes first. The number of
+    /// compared moves of the greedy continuations is returned as well.
+    pub fn choose(
+        &self,
+        state: &State,
+        t_id: TId,
+        candidates: &[Move],
+        trains: usize,
+        rules: &[Rule],
+        model: &Model,
+    ) -> (Option<Move>, usize) {
+        let mut best: Option<(TimeDiff, Move)> = Option::None;
+        let mut checked_moves = 0;
+
+        for &m in candidates.iter().take(self.width) {
+            let mut scratch = state.clone();
+
+            scratch.push(m, model);
+            greedy_tick(
+                &mut scratch,
+                t_id + 1,
+                trains,
+                rules,
+                model,
+                &mut checked_moves,
+            );
+
+            for _ in 0..self.depth {
+                if scratch.t >= model.t_max || scratch.p_arrived.len() == model.passengers.len() {
+                    break;
+                }
+
+                scratch.next(model);
+
+                if !scratch.is_legal() {
+                    break;
+                }
+
+                // the continuation detects the deadlocks like the search
+                if state.deadlocked.is_some() {
+                    scratch.detect_deadlocks(model);
+                }
+
+                greedy_tick(&mut scratch, 0, trains, rules, model, &mut checked_moves);
+            }
+
+            let delay = match scratch.is_legal() {
+                true => estimate(&scratch, model),
+                false => TimeDiff::MAX,
+            };
+
+            // ties are decided by the rules
+            if best.map_or(true, |(best_delay, _)| delay < best_delay) {
+                best = Some((delay, m));
+            }
+        }
+
+        (best.map(|(_, m)| m), checked_moves)
+    }
+}
+
+/// Orders the given moves by the rules and keeps the best `width` moves.
+pub fn rank(
+    mut moves: Vec<Move>,
+    width: usize,
+    rules: &[Rule],
+    state: &State,
+    model: &Model,
+    checked_moves: &mut usize,
+) -> Vec<Move> {
+    let mut ranked = vec![];
+
+    while ranked.len() < width && !moves.is_empty() {
+        let mut best = 0;
+
+        for i in 1..moves.len() {
+            *checked_moves += 1;
+
+            if moves[i].is_gt_with(&moves[best], rules, state, model) {
+                best = i;
+            }
+        }
+
+        ranked.push(moves.remove(best));
+    }
+
+    ranked
+}
+
+/// Moves all trains starting at the given train id that have not been moved
+/// at this point in time by the best move of the rules.
+fn greedy_tick(
+    state: &mut State,
+    from: TId,
+    trains: usize,
+    rules: &[Rule],
+    model: &Model,
+    checked_moves: &mut usize,
+) {
+    let move_none = Move::None(None());
+
+    for t_id in from..trains {
+        if state.train_move(t_id).is_some() {
+            continue;
+        }
+
+        let mut best_move = move_none;
+
+        for m in state.get_moves(t_id, model) {
+            *checked_moves += 1;
+
+            if m.is_gt_with(&best_move, rules, state, model)
+                && m.is_gt_with(&move_none, rules, state, model)
+            {
+                best_move = m;
+            }
+        }
+
+        if let Move::None(_) = best_move {
+        } else {
+            state.push(best_move, model);
+        }
+    }
+}
+
+/// Estimates the total delay of the given state: passengers that have not
+/// arrived yet travel to the destination without any detour or waiting.
+/// Passengers that cannot arrive until `t_max` are charged `t_max`, like
+/// passengers that have not arrived in the fitness of a solution.
+pub fn estimate(state: &State, model: &Model) -> TimeDiff {
+    model
+        .passengers
+        .iter()
+        .enumerate()
+        .map(|(p_id, passenger)| {
+            let arrival: Time = match state.p_location[p_id] {
+                PLocation::Arrived => return state.p_delays[p_id].max(0),
+                // boarding, departing and the travel time of the fastest train
+                PLocation::Station(s_id) => model
+                    .travel_time(0, s_id, passenger.destination)
+                    .saturating_add(state.t + 2),
+                PLocation::Train(t_id) => match state.t_location[t_id] {
+                    TLocation::Station(s_id) => model
+                        .travel_time(t_id, s_id, passenger.destination)
+                        .saturating_add(state.t + 1),
+                    TLocation::Connection(c_id, s_id, t_start) => {
+                        let at = model
+                            .train_arrival(t_id, c_id)
+                            .to_usize()
+                            .map_or(Time::MAX, |ticks| ticks.saturating_add(t_start));
+
+                        model
+                            .travel_time(t_id, s_id, passenger.destination)
+                            .saturating_add(at.max(state.t))
+                    }
+                    TLocation::Nothing => state.t,
+                },
+            };
+
+            if arrival > model.t_max {
+                return model.t_max as TimeDiff;
+            }
+
+            (arrival as TimeDiff - passenger.arrival as TimeDiff).max(0)
+        })
+        .sum()
+}
+
+#[test]
+fn it_charges_passengers_that_cannot_arrive_t_max() {
+    let mut model =
+        crate::parser::parse(&include_str!("../test-cases/simple/input.txt").to_owned());
+
+    // no passenger can arrive until t_max
+    model.t_max = 2;
+
+    let state = model.initial_state();
+
+    assert_eq!(
+        estimate(&state, &model),
+        model.passengers.len() as TimeDiff * 2
+    );
+    assert_eq!(
+        estimate(&state, &model),
+        crate::solution::Solution(vec![state]).fitness()
+    );
+}
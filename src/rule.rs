@@ -182,3 +182,91 @@ impl Rule {
         Result::None
     }
 }
+
+/// Counts how often each rule has returned `Some(true)`, `Some(false)` or
+/// `None` when moves have been compared, and how often each rule has decided
+/// the move that has been chosen.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Statistics {
+    pub some_true: Vec<usize>,
+    pub some_false: Vec<usize>,
+    pub none: Vec<usize>,
+    pub decided: Vec<usize>,
+}
+
+impl Statistics {
+    /// Constructs empty statistics for the given number of rules.
+    pub fn new(rules: usize) -> Statistics {
+        Statistics {
+            some_true: vec![0; rules],
+            some_false: vec![0; rules],
+            none: vec![0; rules],
+            decided: vec![0; rules],
+        }
+    }
+
+    /// Records a comparison whose result has been decided by the given rule,
+    /// all rules before have returned `None`, see
+    /// [deciding_rule](crate::move_::Move::deciding_rule).
+    pub fn record(&mut self, decision: Option<(usize, bool)>) {
+        let evaluated = decision.map_or(self.none.len(), |(i, _)| i);
+
+        for none in &mut self.none[..evaluated] {
+            *none += 1;
+        }
+
+        match decision {
+            Some((i, true)) => self.some_true[i] += 1,
+            Some((i, false)) => self.some_false[i] += 1,
+            Option::None => (),
+        }
+    }
+
+    /// Adds the counts of the given statistics.
+    pub fn add(&mut self, other: &Statistics) {
+        for (counts, others) in [
+            (&mut self.some_true, &other.some_true),
+            (&mut self.some_false, &other.some_false),
+            (&mut self.none, &other.none),
+            (&mut self.decided, &other.decided),
+        ] {
+            for (count, other) in counts.iter_mut().zip(others.iter()) {
+                *count += other;
+            }
+        }
+    }
+
+    /// Gets the statistics as JSON array with an object for each rule, the
+    /// rules are named by the given labels, see
+    /// [get_rule_labels](crate::rules::get_rule_labels).
+    pub fn to_json(&self, labels: &[String]) -> String {
+        let rules: Vec<String> = (0..self.none.len())
+            .map(|i| {
+                let label = labels.get(i).cloned().unwrap_or(format!("rule {}", i));
+
+                format!(
+                    "  {{\"rule\": \"{}\", \"true\": {}, \"false\": {}, \"none\": {}, \"decided\": {}}}",
+                    label.replace('\\', "\\\\").replace('"', "\\\""),
+                    self.some_true[i],
+                    self.some_false[i],
+                    self.none[i],
+                    self.decided[i]
+                )
+            })
+            .collect();
+
+        format!("[\n{}\n]\n", rules.join(",\n"))
+    }
+}
+
+#[test]
+fn it_counts_the_rules_before_the_deciding_rule_as_none() {
+    let mut statistics = Statistics::new(3);
+
+    statistics.record(Some((1, true)));
+    statistics.record(Option::None);
+
+    assert_eq!(statistics.none, vec![2, 1, 1]);
+    assert_eq!(statistics.some_true, vec![0, 1, 0]);
+    assert_eq!(statistics.some_false, vec![0, 0, 0]);
+}
@@ -0,0 +1,151 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::passenger::Id as PId;
+use crate::rule::{Closure, Result, Rule};
+use crate::state::State;
+use crate::station::Id as SId;
+use crate::train::{Id as TId, Location as TLocation};
+use crate::types::Time;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Gets another train that brings the passenger from an intermediate station
+/// to the destination sooner than the given train, including the time until
+/// the other train arrives and the boarding. The other train has to wait at
+/// the station or arrive there, must have room for the passenger group and
+/// must head along the travel path of the passenger, i.e. it carries a
+/// passenger whose travel path shares the destination like in
+/// [board_by_travel_path](crate::rules::board_by_travel_path).
+pub fn transfer_train(
+    p_id: PId,
+    t_id: TId,
+    s_id: SId,
+    state: &State,
+    model: &Model,
+) -> Option<TId> {
+    let passenger = &model.passengers[p_id];
+
+    // transfers happen at intermediate stations only
+    if s_id == passenger.start || s_id == passenger.destination {
+        return None;
+    }
+
+    let direct = model.travel_time(t_id, s_id, passenger.destination);
+    let path = &model.paths.get(&(s_id, passenger.destination))?.path;
+    let heads_along_path = |other: TId| {
+        state.t_passengers[other].iter().any(|&p_id| {
+            let destination = model.passengers[p_id].destination;
+
+            path.contains(&destination)
+                || model
+                    .paths
+                    .get(&(s_id, destination))
+                    .map_or(false, |other| other.path.contains(&passenger.destination))
+        })
+    };
+
+    (0..model.trains.len())
+        .filter(|&other| other != t_id && state.t_capacity[other] >= passenger.size)
+        .filter(|&other| heads_along_path(other))
+        .filter_map(|other| {
+            let wait: Time = match state.t_location[other] {
+                TLocation::Station(at)
+                    if at == s_id && !matches!(state.train_move(other), Some(Move::Depart(_))) =>
+                {
+                    0
+                }
+                TLocation::Connection(c_id, to, t_start) if to == s_id => {
+                    let arrival = t_start + model.train_arrival(other, c_id).to_usize()?;
+
+                    arrival.saturating_sub(state.t)
+                }
+                _ => return None,
+            };
+            let arrival =
+                (wait + 1).saturating_add(model.travel_time(other, s_id, passenger.destination));
+
+            (arrival < direct).then_some((arrival, other))
+        })
+        .min()
+        .map(|(_, other)| other)
+}
+
+/// Passengers should detrain at an intermediate station when another train
+/// picks them up there and brings them to their destination sooner, before
+/// other passengers board.
+pub fn rules() -> Vec<Rule> {
+    vec![
+        Rule::IsBoardGtDetrain(Closure {
+            c: Box::new(|_, b, state, model| {
+                match transfer_train(b.p_id, b.t_id, b.s_id, state, model) {
+                    Some(_) => Result::Some(false),
+                    None => Result::None,
+                }
+            }),
+        }),
+        Rule::IsDetrainGtDepart(Closure {
+            c: Box::new(|a, _, state, model| {
+                match transfer_train(a.p_id, a.t_id, a.s_id, state, model) {
+                    Some(_) => Result::Some(true),
+                    None => Result::None,
+                }
+            }),
+        }),
+        Rule::IsDetrainGtNone(Closure {
+            c: Box::new(|a, _, state, model| {
+                match transfer_train(a.p_id, a.t_id, a.s_id, state, model) {
+                    Some(_) => Result::Some(true),
+                    None => Result::None,
+                }
+            }),
+        }),
+    ]
+}
+
+#[test]
+fn it_transfers_to_a_faster_train() {
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 2
+S2 2
+S3 2
+[Lines]
+L1 S1 S2 1 2
+L2 S2 S3 8 2
+[Trains]
+T1 S2 4 6
+T2 S1 1 5
+[Passengers]
+P1 S1 S3 5 10
+P2 S2 S3 1 10
+"
+        .to_owned(),
+    );
+    let mut state = model.initial_state();
+
+    // T2 carries P1 to S2, where the faster T1 is waiting
+    state.t_location[1] = TLocation::Station(1);
+    state.t_passengers[1].insert(0);
+    state.t_capacity[1] -= 5;
+
+    // an empty train does not head along the travel path
+    assert_eq!(transfer_train(0, 1, 1, &state, &model), None);
+
+    state.t_passengers[0].insert(1);
+    state.t_capacity[0] -= 1;
+
+    assert_eq!(transfer_train(0, 1, 1, &state, &model), Some(0));
+    assert_eq!(transfer_train(0, 0, 1, &state, &model), None);
+
+    // the detrained passenger waits for the other train
+    let detrain = Move::Detrain(crate::move_::Detrain {
+        t_id: 1,
+        p_id: 0,
+        s_id: 1,
+    });
+
+    state.push(detrain, &model);
+    assert!(state.s_passengers[1].contains(&0));
+    state.pop(&model);
+    assert!(!state.s_passengers[1].contains(&0));
+}
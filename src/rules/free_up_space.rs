@@ -7,7 +7,7 @@ pub fn rules() -> Vec<Rule> {
         // depart vs none
         Rule::IsDepartGtNone(Closure {
             c: Box::new(|a, _, state, _| {
-                if state.s_capacity[a.from] == 0 && state.s_passengers[a.from].len() == 0 {
+                if state.s_capacity[a.from] == 0 && state.s_passengers[a.from].is_empty() {
                     Result::Some(true)
                 } else {
                     Result::None
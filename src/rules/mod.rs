@@ -9,38 +9,161 @@ mod depart_to_exact_destination;
 mod depart_to_pickup_passenger;
 mod depart_towards_destination;
 mod detrain_arrived_passenger;
+pub mod detrain_to_transfer;
+mod free_up_space;
+mod reposition_empty_trains;
+mod resolve_deadlocks;
 
 use crate::rule::Rule;
 
+/// The names of all rule sets, which can be selected at runtime.
+pub const RULE_SETS: [&str; 15] = [
+    "avoid_station_overload",
+    "resolve_deadlocks",
+    "detrain_arrived_passenger",
+    "detrain_to_transfer",
+    "board_by_arrival",
+    "board_by_destination",
+    "board_by_travel_path",
+    "board_to_empty_trains",
+    "depart_to_exact_destination",
+    "depart_towards_destination",
+    "depart_passenger_trains",
+    "depart_to_pickup_passenger",
+    "reposition_empty_trains",
+    "free_up_space",
+    "choose_train_starts",
+];
+
+/// The rule sets that are applied by default in the following order.
+pub const DEFAULT_RULE_SETS: [&str; 10] = [
+    "avoid_station_overload",
+    "detrain_arrived_passenger",
+    "board_by_arrival",
+    "board_by_travel_path",
+    "board_to_empty_trains",
+    "depart_to_exact_destination",
+    "depart_towards_destination",
+    "depart_passenger_trains",
+    "depart_to_pickup_passenger",
+    "choose_train_starts",
+];
+
+/// Gets the rules of the rule set with the given name.
+pub fn get_rule_set(name: &str) -> Option<Vec<Rule>> {
+    match name {
+        "avoid_station_overload" => Some(avoid_station_overload::rules()),
+        "resolve_deadlocks" => Some(resolve_deadlocks::rules()),
+        "detrain_arrived_passenger" => Some(detrain_arrived_passenger::rules()),
+        "detrain_to_transfer" => Some(detrain_to_transfer::rules()),
+        "board_by_arrival" => Some(board_by_arrival::rules()),
+        "board_by_destination" => Some(board_by_destination::rules()),
+        "board_by_travel_path" => Some(board_by_travel_path::rules()),
+        "board_to_empty_trains" => Some(board_to_empty_trains::rules()),
+        "depart_to_exact_destination" => Some(depart_to_exact_destination::rules()),
+        "depart_towards_destination" => Some(depart_towards_destination::rules()),
+        "depart_passenger_trains" => Some(depart_passenger_trains::rules()),
+        "depart_to_pickup_passenger" => Some(depart_to_pickup_passenger::rules()),
+        "reposition_empty_trains" => Some(reposition_empty_trains::rules()),
+        "free_up_space" => Some(free_up_space::rules()),
+        "choose_train_starts" => Some(choose_train_starts::rules()),
+        _ => None,
+    }
+}
+
+/// Gets the rules of the given rule sets, the rule sets are applied in the
+/// given order.
+pub fn get_rules_by_names<S: AsRef<str>>(names: &[S]) -> Result<Vec<Rule>, String> {
+    let mut rules = vec![];
+
+    for name in names {
+        match get_rule_set(name.as_ref()) {
+            Some(rule_set) => rules.extend(rule_set),
+            None => {
+                return Err(format!(
+                    "Unknown rule set \"{}\", available rule sets: {}",
+                    name.as_ref(),
+                    RULE_SETS.join(", ")
+                ))
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Determines whether the given rule sets compare moves by the deadlocks of
+/// the state, which are only detected when they are required, see
+/// [detect_deadlocks](crate::search::SearchConfig::detect_deadlocks).
+pub fn requires_deadlocks<S: AsRef<str>>(names: &[S]) -> bool {
+    names
+        .iter()
+        .any(|name| name.as_ref() == "resolve_deadlocks")
+}
+
+/// Parses a list of rule set names separated by commas or new lines, lines
+/// starting with `#` and the weights of the score rules are ignored, see
+/// [parse_weights](crate::score::parse_weights).
+pub fn parse_rule_set_names(string: &str) -> Vec<&str> {
+    string
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+
+            !line.starts_with('#') && !line.starts_with("weights ")
+        })
+        .flat_map(|line| line.split(','))
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Parses a list of rule set names and gets the rules of the rule sets, see
+/// [parse_rule_set_names]. At least one rule set has to be given.
+pub fn parse_rule_sets(string: &str) -> Result<Vec<Rule>, String> {
+    let names = parse_rule_set_names(string);
+
+    if names.is_empty() {
+        return Err(format!(
+            "No rule set given, available rule sets: {}",
+            RULE_SETS.join(", ")
+        ));
+    }
+
+    get_rules_by_names(&names)
+}
+
+/// Gets a label for each rule of the given rule sets, which consists of the
+/// name of the rule set and the position of the rule within the set, e.g.
+/// `board_by_arrival#2`. Unknown rule sets are skipped.
+pub fn get_rule_labels<S: AsRef<str>>(names: &[S]) -> Vec<String> {
+    names
+        .iter()
+        .flat_map(|name| {
+            let len = get_rule_set(name.as_ref()).map_or(0, |rules| rules.len());
+
+            (1..=len).map(move |i| format!("{}#{}", name.as_ref(), i))
+        })
+        .collect()
+}
+
 pub fn get_rules() -> Vec<Rule> {
-    vec![
-        // rules are applied in the following order:
-        //
-        avoid_station_overload::rules(),
-        //
-        detrain_arrived_passenger::rules(),
-        //
-        board_by_arrival::rules(),
-        //
-        // board_by_destination::rules(),
-        //
-        board_by_travel_path::rules(),
-        //
-        board_to_empty_trains::rules(),
-        //
-        depart_to_exact_destination::rules(),
-        //
-        depart_towards_destination::rules(),
-        //
-        depart_passenger_trains::rules(),
-        //
-        depart_to_pickup_passenger::rules(),
-        //
-        // // free_up_space::rules(),
-        // //
-        choose_train_starts::rules(),
-    ]
-    .into_iter()
-    .flatten()
-    .collect()
+    get_rules_by_names(&DEFAULT_RULE_SETS).expect("Unknown default rule set")
+}
+
+#[test]
+fn it_parses_rule_sets_by_name() {
+    let rules = parse_rule_sets("# comment\navoid_station_overload, free_up_space\n").unwrap();
+
+    assert_eq!(
+        rules.len(),
+        avoid_station_overload::rules().len() + free_up_space::rules().len()
+    );
+    assert!(parse_rule_sets("avoid_station_overload,unknown").is_err());
+    assert!(parse_rule_sets(",").is_err());
+    assert!(parse_rule_sets("# comment\n").is_err());
+    assert_eq!(
+        get_rule_labels(&["avoid_station_overload"]),
+        vec!["avoid_station_overload#1", "avoid_station_overload#2"]
+    );
 }
@@ -0,0 +1,115 @@
+use crate::model::Model;
+use crate::rule::{Closure, Result, Rule};
+use crate::state::State;
+use crate::station::Id as SId;
+use crate::train::{Id as TId, Location as TLocation};
+use crate::types::TimeDiff;
+use std::convert::TryFrom;
+
+/// Gets the station an empty train should pick up passengers at: the station
+/// with the most urgent waiting passenger that fits into the train, i.e. the
+/// least slack until the arrival time when the train picks the passenger up
+/// and brings it to its destination. Closer stations are preferred on equal
+/// slack. Stations that another empty train reaches sooner are left to that
+/// train, hence several trains are not sent to the same pickup.
+pub fn pickup_station(t_id: TId, s_id: SId, state: &State, model: &Model) -> Option<SId> {
+    let travel_time =
+        |t_id, a, b| TimeDiff::try_from(model.travel_time(t_id, a, b)).unwrap_or(TimeDiff::MAX);
+
+    // the empty trains at a station or on the way to a station
+    let others: Vec<(TId, SId)> = (0..model.trains.len())
+        .filter(|&other| other != t_id && state.t_passengers[other].is_empty())
+        .filter_map(|other| match state.t_location[other] {
+            TLocation::Station(at) | TLocation::Connection(_, at, _) => Some((other, at)),
+            TLocation::Nothing => None,
+        })
+        .collect();
+
+    model.closest_stations[s_id]
+        .iter()
+        .filter(|closest| closest.s_id != s_id && !state.s_passengers[closest.s_id].is_empty())
+        .filter_map(|closest| {
+            let pickup = travel_time(t_id, s_id, closest.s_id);
+
+            if others
+                .iter()
+                .any(|&(other, at)| travel_time(other, at, closest.s_id) < pickup)
+            {
+                return None;
+            }
+
+            let slack = state.s_passengers[closest.s_id]
+                .iter()
+                .map(|&p_id| &model.passengers[p_id])
+                .filter(|passenger| passenger.size <= state.t_capacity[t_id])
+                .map(|passenger| {
+                    (passenger.arrival as TimeDiff - state.t as TimeDiff)
+                        .saturating_sub(pickup)
+                        .saturating_sub(travel_time(t_id, closest.s_id, passenger.destination))
+                })
+                .min()?;
+
+            Some((slack, closest.distance, closest.s_id))
+        })
+        .min()
+        .map(|(_, _, pickup)| pickup)
+}
+
+/// Empty trains at stations without waiting passengers should depart towards
+/// the station with the most urgent waiting passengers.
+pub fn rules() -> Vec<Rule> {
+    vec![Rule::IsDepartGtDepart(Closure {
+        c: Box::new(|a, b, state, model| {
+            if !state.t_passengers[a.t_id].is_empty() || !state.s_passengers[a.from].is_empty() {
+                return Result::None;
+            }
+
+            match pickup_station(a.t_id, a.from, state, model) {
+                Some(s_id) => {
+                    let a_distance = model.distance(a.to, s_id);
+                    let b_distance = model.distance(b.to, s_id);
+
+                    if a_distance == b_distance {
+                        Result::None
+                    } else {
+                        Result::Some(a_distance < b_distance)
+                    }
+                }
+                None => Result::None,
+            }
+        }),
+    })]
+}
+
+#[test]
+fn it_sends_empty_trains_to_different_pickups() {
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 2
+S2 2
+S3 2
+S4 2
+[Lines]
+L1 S1 S2 1 2
+L2 S2 S3 1 2
+L3 S3 S4 1 2
+[Trains]
+T1 S1 1 5
+T2 S4 1 5
+[Passengers]
+P1 S2 S1 1 20
+P2 S3 S4 1 3
+"
+        .to_owned(),
+    );
+    let mut state = model.initial_state();
+
+    // P2 is more urgent, but T2 picks it up sooner
+    assert_eq!(pickup_station(0, 0, &state, &model), Some(1));
+    assert_eq!(pickup_station(1, 3, &state, &model), Some(2));
+
+    state.t_location[1] = TLocation::Nothing;
+
+    assert_eq!(pickup_station(0, 0, &state, &model), Some(2));
+}
@@ -0,0 +1,144 @@
+use crate::model::Model;
+use crate::move_::Depart;
+use crate::rule::{Closure, Result, Rule};
+use crate::state::State;
+
+/// Determines whether the departure resolves a deadlock: the train waits for
+/// other trains in a cycle and departs to a neighbouring station with free
+/// capacity, which frees its station for the other trains.
+fn resolves_deadlock(depart: &Depart, state: &State, model: &Model) -> bool {
+    state.est_s_cap(model.t_max, depart.to, model) > 0
+        && state.next_station(depart.t_id, model) != Some(depart.to)
+        && state.is_deadlocked(depart.t_id, model)
+}
+
+/// Trains that wait for each other in a cycle should give way by departing to
+/// a free neighbouring station.
+pub fn rules() -> Vec<Rule> {
+    vec![
+        Rule::IsDepartGtDepart(Closure {
+            c: Box::new(|a, b, state, model| {
+                match (
+                    resolves_deadlock(a, state, model),
+                    resolves_deadlock(b, state, model),
+                ) {
+                    (true, false) => Result::Some(true),
+                    (false, true) => Result::Some(false),
+                    _ => Result::None,
+                }
+            }),
+        }),
+        Rule::IsDepartGtNone(Closure {
+            c: Box::new(|a, _, state, model| {
+                if resolves_deadlock(a, state, model) {
+                    Result::Some(true)
+                } else {
+                    Result::None
+                }
+            }),
+        }),
+    ]
+}
+
+#[test]
+fn it_resolves_trains_waiting_for_each_other() {
+    use crate::move_::{Board, Move};
+
+    let model = crate::parser::parse(
+        &"
+[Stations]
+S1 1
+S2 1
+S3 1
+[Lines]
+L1 S1 S2 2 1
+L2 S1 S3 2 1
+[Trains]
+T1 S1 1 5
+T2 S2 1 5
+[Passengers]
+P1 S1 S2 5 6
+P2 S2 S1 5 6
+"
+        .to_owned(),
+    );
+    let mut state = model.initial_state();
+
+    state.next(&model);
+    state.push(
+        Move::Board(Board {
+            t_id: 0,
+            p_id: 0,
+            s_id: 0,
+        }),
+        &model,
+    );
+    state.push(
+        Move::Board(Board {
+            t_id: 1,
+            p_id: 1,
+            s_id: 1,
+        }),
+        &model,
+    );
+
+    // both stations are full and each train wants to go to the other one
+    assert_eq!(state.deadlocks(&model), vec![vec![0, 1]]);
+
+    let depart = |to, c_id| Depart {
+        t_id: 0,
+        from: 0,
+        to,
+        c_id,
+    };
+
+    assert!(resolves_deadlock(&depart(2, 1), &state, &model));
+    assert!(!resolves_deadlock(&depart(1, 0), &state, &model));
+
+    // the detected deadlocks hold until the next point in time
+    state.detect_deadlocks(&model);
+    assert!(resolves_deadlock(&depart(2, 1), &state, &model));
+    state.next(&model);
+    assert!(state.deadlocked.is_none());
+}
+
+#[test]
+fn it_resolves_trains_waiting_for_parked_trains() {
+    use crate::move_::{Board, Move};
+
+    let model = crate::parser::parse(
+        &include_str!("../../test-cases/stationCapacity/input.txt").to_owned(),
+    );
+    let mut state = model.initial_state();
+
+    state.next(&model);
+    state.push(
+        Move::Board(Board {
+            t_id: 0,
+            p_id: 0,
+            s_id: 0,
+        }),
+        &model,
+    );
+
+    // the parked train blocks the destination, but the passenger can still
+    // arrive in time
+    assert!(state.deadlocks(&model).is_empty());
+
+    while state.t < 3 {
+        state.next(&model);
+    }
+
+    // the passenger is late unless the parked train gives way
+    assert_eq!(state.deadlocks(&model), vec![vec![0, 1]]);
+
+    let depart = |t_id, from, to, c_id| Depart {
+        t_id,
+        from,
+        to,
+        c_id,
+    };
+
+    assert!(resolves_deadlock(&depart(1, 1, 2, 1), &state, &model));
+    assert!(!resolves_deadlock(&depart(0, 0, 1, 0), &state, &model));
+}
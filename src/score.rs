@@ -0,0 +1,392 @@
+use crate::model::Model;
+use crate::move_::Move;
+use crate::state::State;
+use rust_decimal::prelude::ToPrimitive;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// A rule that scores a single move instead of comparing two moves, higher
+/// scores are better.
+///
+/// Unlike the pairwise [Rule](crate::rule::Rule), the scores of all moves
+/// form a consistent ranking, which does not depend on the order the moves
+/// are compared in.
+pub trait ScoreRule {
+    /// Gets the name of the rule.
+    fn name(&self) -> &'static str;
+
+    /// Gets the score of the move, `None` is returned when the rule abstains,
+    /// which counts as a score of `0`.
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64>;
+}
+
+/// The way the scores of the rules are combined to rank the moves.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Ranking {
+    /// The scores are compared rule by rule, the first rule with different
+    /// scores decides.
+    Lexicographic,
+    /// The weighted sum of the scores is compared, one weight per rule. The
+    /// default weights are used when no weights are given.
+    Weighted(Vec<f64>),
+}
+
+impl FromStr for Ranking {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "lexicographic" => Ok(Ranking::Lexicographic),
+            "weighted" => Ok(Ranking::Weighted(vec![])),
+            _ => Err(format!("Unknown ranking \"{}\"!", string)),
+        }
+    }
+}
+
+/// Parses the weights of the weighted ranking from a rules file, where they
+/// are given in a line like `weights 256,128,64,32,16,8,4,2,1`. `None` is
+/// returned when the rules file has no weights.
+pub fn parse_weights(string: &str) -> Result<Option<Vec<f64>>, String> {
+    let line = match string
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with("weights "))
+    {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    let weights: Vec<f64> = line["weights ".len()..]
+        .split(',')
+        .map(|w| w.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid weights \"{}\"!", line))?;
+
+    match weights.len() == default_rules().len() {
+        true => Ok(Some(weights)),
+        false => Err(format!(
+            "Expected {} weights, one per score rule!",
+            default_rules().len()
+        )),
+    }
+}
+
+/// Score rules and the way their scores are combined.
+pub struct Scoring {
+    pub rules: Vec<Box<dyn ScoreRule>>,
+    pub ranking: Ranking,
+}
+
+impl Scoring {
+    /// Constructs the default score rules with the given ranking. A weighted
+    /// ranking without weights halves the weight of each rule compared to
+    /// the previous rule, which keeps the priority of the rules.
+    pub fn new(ranking: Ranking) -> Scoring {
+        let ranking = match ranking {
+            Ranking::Weighted(weights) if weights.is_empty() => {
+                Ranking::Weighted(default_weights())
+            }
+            ranking => ranking,
+        };
+
+        Scoring {
+            rules: default_rules(),
+            ranking,
+        }
+    }
+
+    /// Gets the score of each rule for the given move.
+    pub fn scores(&self, m: &Move, state: &State, model: &Model) -> Vec<f64> {
+        self.rules
+            .iter()
+            .map(|rule| rule.score(m, state, model).unwrap_or(0.0))
+            .collect()
+    }
+
+    /// Compares the scores of two moves.
+    pub fn compare(&self, a: &[f64], b: &[f64]) -> Ordering {
+        match &self.ranking {
+            Ranking::Lexicographic => a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal),
+            Ranking::Weighted(weights) => {
+                let sum = |scores: &[f64]| -> f64 {
+                    scores.iter().zip(weights.iter()).map(|(s, w)| s * w).sum()
+                };
+
+                sum(a).total_cmp(&sum(b))
+            }
+        }
+    }
+
+    /// Ranks the moves that are better than no move, the best move comes
+    /// first. Each move is scored once, moves with equal scores keep their
+    /// order.
+    pub fn rank(&self, moves: Vec<Move>, state: &State, model: &Model) -> Vec<Move> {
+        let none = self.scores(&Move::None(crate::move_::None()), state, model);
+        let mut scored: Vec<(Vec<f64>, Move)> = moves
+            .into_iter()
+            .map(|m| (self.scores(&m, state, model), m))
+            .filter(|(scores, _)| self.compare(scores, &none).is_gt())
+            .collect();
+
+        scored.sort_by(|(a, _), (b, _)| self.compare(b, a));
+        scored.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+/// Gets the default score rules in the order of their priority.
+pub fn default_rules() -> Vec<Box<dyn ScoreRule>> {
+    vec![
+        Box::new(AvoidStationOverload),
+        Box::new(DetrainArrivedPassenger),
+        Box::new(BoardSharedPath),
+        Box::new(DepartToExactDestination),
+        Box::new(DepartPassengerTrains),
+        Box::new(DepartTowardsDestination),
+        Box::new(BoardByArrival),
+        Box::new(DepartToPickupPassenger),
+        Box::new(ChooseTrainStarts),
+    ]
+}
+
+/// Gets the default weights of the score rules, each weight is half of the
+/// weight of the previous rule.
+pub fn default_weights() -> Vec<f64> {
+    let len = default_rules().len();
+
+    (0..len).map(|i| (1 << (len - 1 - i)) as f64).collect()
+}
+
+/// Trains should not depart or start to stations that are estimated to be
+/// full.
+pub struct AvoidStationOverload;
+
+impl ScoreRule for AvoidStationOverload {
+    fn name(&self) -> &'static str {
+        "avoid_station_overload"
+    }
+
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64> {
+        match m {
+            Move::Depart(depart) => {
+                let capacity = state.est_s_cap(model.t_max, depart.to, model);
+
+                (capacity - 1 <= -model.stations[depart.to].capacity).then_some(-1.0)
+            }
+            Move::Start(start) => {
+                (state.est_s_cap(model.t_max, start.s_id, model) <= 0).then_some(-1.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Passengers should detrain at their destination.
+pub struct DetrainArrivedPassenger;
+
+impl ScoreRule for DetrainArrivedPassenger {
+    fn name(&self) -> &'static str {
+        "detrain_arrived_passenger"
+    }
+
+    fn score(&self, m: &Move, _: &State, model: &Model) -> Option<f64> {
+        match m {
+            Move::Detrain(detrain) => {
+                (detrain.s_id == model.passengers[detrain.p_id].destination).then_some(1.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Passengers should board empty trains or trains with passengers that share
+/// the travel path.
+pub struct BoardSharedPath;
+
+impl ScoreRule for BoardSharedPath {
+    fn name(&self) -> &'static str {
+        "board_shared_path"
+    }
+
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64> {
+        let board = match m {
+            Move::Board(board) => board,
+            _ => return None,
+        };
+
+        if state.t_passengers[board.t_id].is_empty() {
+            return Some(1.0);
+        }
+
+        let destination = model.passengers[board.p_id].destination;
+        let path = &model.paths.get(&(board.s_id, destination))?.path;
+
+        state.t_passengers[board.t_id]
+            .iter()
+            .any(|&p_id| {
+                let other = model.passengers[p_id].destination;
+
+                path.contains(&other)
+                    || model
+                        .paths
+                        .get(&(board.s_id, other))
+                        .map_or(false, |other| other.path.contains(&destination))
+            })
+            .then_some(1.0)
+    }
+}
+
+/// Trains should depart to the destination of one of their passengers.
+pub struct DepartToExactDestination;
+
+impl ScoreRule for DepartToExactDestination {
+    fn name(&self) -> &'static str {
+        "depart_to_exact_destination"
+    }
+
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64> {
+        match m {
+            Move::Depart(depart) => state.t_passengers[depart.t_id]
+                .iter()
+                .any(|&p_id| model.passengers[p_id].destination == depart.to)
+                .then_some(1.0),
+            _ => None,
+        }
+    }
+}
+
+/// Trains with passengers should depart.
+pub struct DepartPassengerTrains;
+
+impl ScoreRule for DepartPassengerTrains {
+    fn name(&self) -> &'static str {
+        "depart_passenger_trains"
+    }
+
+    fn score(&self, m: &Move, state: &State, _: &Model) -> Option<f64> {
+        match m {
+            Move::Depart(depart) => (!state.t_passengers[depart.t_id].is_empty()).then_some(1.0),
+            _ => None,
+        }
+    }
+}
+
+/// Trains should depart towards the destination of the most urgent passenger,
+/// the score decreases with the remaining distance.
+pub struct DepartTowardsDestination;
+
+impl ScoreRule for DepartTowardsDestination {
+    fn name(&self) -> &'static str {
+        "depart_towards_destination"
+    }
+
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64> {
+        let depart = match m {
+            Move::Depart(depart) => depart,
+            _ => return None,
+        };
+
+        let p_id = *state.t_passengers[depart.t_id]
+            .iter()
+            .min_by_key(|&&p_id| (model.passengers[p_id].arrival, p_id))?;
+        let distance = model.distance(depart.to, model.passengers[p_id].destination);
+
+        Some(1.0 / (1.0 + distance.to_f64()?))
+    }
+}
+
+/// Passengers with an early arrival are boarded first, the score decreases
+/// with the arrival time.
+pub struct BoardByArrival;
+
+impl ScoreRule for BoardByArrival {
+    fn name(&self) -> &'static str {
+        "board_by_arrival"
+    }
+
+    fn score(&self, m: &Move, _: &State, model: &Model) -> Option<f64> {
+        match m {
+            Move::Board(board) => Some(
+                1.0 - model.passengers[board.p_id].arrival as f64 / (model.max_arrival + 1) as f64,
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Empty trains at stations without waiting passengers should depart to pick
+/// up passengers elsewhere.
+pub struct DepartToPickupPassenger;
+
+impl ScoreRule for DepartToPickupPassenger {
+    fn name(&self) -> &'static str {
+        "depart_to_pickup_passenger"
+    }
+
+    fn score(&self, m: &Move, state: &State, _: &Model) -> Option<f64> {
+        match m {
+            Move::Depart(depart) => (state.t_passengers[depart.t_id].is_empty()
+                && state.s_passengers[depart.from].is_empty())
+            .then_some(1.0),
+            _ => None,
+        }
+    }
+}
+
+/// Trains should start at stations with passengers that fit into the train,
+/// stations with early arrivals are preferred.
+pub struct ChooseTrainStarts;
+
+impl ScoreRule for ChooseTrainStarts {
+    fn name(&self) -> &'static str {
+        "choose_train_starts"
+    }
+
+    fn score(&self, m: &Move, state: &State, model: &Model) -> Option<f64> {
+        let start = match m {
+            Move::Start(start) => start,
+            _ => return None,
+        };
+
+        let arrivals: Vec<f64> = state.s_passengers[start.s_id]
+            .iter()
+            .filter(|&&p_id| model.passengers[p_id].size <= state.t_capacity[start.t_id])
+            .map(|&p_id| model.passengers[p_id].arrival as f64)
+            .collect();
+
+        match arrivals.is_empty() {
+            true => None,
+            false => Some(1.0 / (1.0 + arrivals.iter().sum::<f64>())),
+        }
+    }
+}
+
+#[test]
+fn it_ranks_moves_by_their_scores() {
+    let scoring = Scoring::new(Ranking::Lexicographic);
+
+    assert_eq!(
+        scoring.compare(&[1.0, -5.0], &[0.0, 3.0]),
+        Ordering::Greater
+    );
+    assert_eq!(scoring.compare(&[0.0, -5.0], &[0.0, 3.0]), Ordering::Less);
+
+    // the default weights keep the priority of scores within [0, 1]
+    let weighted = Scoring::new(Ranking::Weighted(vec![]));
+    let mut a = vec![0.0; weighted.rules.len()];
+    let mut b = vec![1.0; weighted.rules.len()];
+
+    a[0] = 1.0;
+    b[0] = 0.0;
+    assert_eq!(weighted.compare(&a, &b), Ordering::Greater);
+
+    let equal = Scoring {
+        rules: vec![],
+        ranking: Ranking::Weighted(vec![1.0, 1.0]),
+    };
+
+    assert_eq!(equal.compare(&[1.0, -5.0], &[0.0, 3.0]), Ordering::Less);
+}
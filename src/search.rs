@@ -0,0 +1,473 @@
+use crate::bound::{flow_lower_bound, passenger_bounds};
+use crate::explain::Decision;
+use crate::model::Model;
+use crate::restart::{Statistics, Strategy};
+use crate::rollout::Rollout;
+use crate::rule::{Rule, Statistics as RuleStatistics};
+use crate::score::Scoring;
+use crate::solution::Solution;
+use crate::tabu_list::{TabuList, EXACT_ENTRY_BYTES};
+use crate::types::{Time, TimeDiff};
+use std::str::FromStr;
+
+/// The objective that is minimized by the search.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Objective {
+    /// The sum of the delays of all passengers.
+    TotalDelay,
+    /// The maximum delay of all passengers.
+    MaxDelay,
+}
+
+impl Objective {
+    /// Gets the name of the objective.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::TotalDelay => "total-delay",
+            Objective::MaxDelay => "max-delay",
+        }
+    }
+
+    /// Gets the fitness of the given solution, lower is better.
+    pub fn fitness(&self, solution: &Solution) -> TimeDiff {
+        match self {
+            Objective::TotalDelay => solution.fitness(),
+            Objective::MaxDelay => solution.max_delay(),
+        }
+    }
+
+    /// Gets a lower bound of the fitness of all solutions of the model, the
+    /// total delay is bound by the flow relaxation, which is at least the sum
+    /// of the passenger bounds.
+    pub fn lower_bound(&self, model: &Model) -> TimeDiff {
+        match self {
+            Objective::TotalDelay => flow_lower_bound(model),
+            Objective::MaxDelay => passenger_bounds(model).into_iter().max().unwrap_or(0),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        [Objective::TotalDelay, Objective::MaxDelay]
+            .iter()
+            .find(|objective| objective.name() == string)
+            .copied()
+            .ok_or_else(|| format!("Unknown objective \"{}\"!", string))
+    }
+}
+
+/// The data structure of the tabu list.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TabuBackend {
+    Exact,
+    Bloom,
+}
+
+/// The configuration of a [TabuGeneticSearch](crate::tabu::TabuGeneticSearch).
+///
+/// ```
+/// use rstrain::search::{Objective, SearchConfig};
+///
+/// let config = SearchConfig::default()
+///     .time(10000)
+///     .seed(42)
+///     .objective(Objective::MaxDelay);
+/// ```
+pub struct SearchConfig {
+    /// The maximum number of milli seconds the search should run.
+    pub max_millis: u128,
+
+    /// The maximum number of iterations without improvement.
+    pub max_iterations: u128,
+
+    /// The maximum number of states in the exact tabu list.
+    pub tabu_size: usize,
+
+    /// The memory budget of the tabu list in bytes, which overrides the tabu
+    /// size.
+    pub tabu_memory: Option<usize>,
+
+    /// The data structure of the tabu list.
+    pub tabu_backend: TabuBackend,
+
+    /// The seed of the random generator, the generator is seeded from the
+    /// operating system when no seed is given.
+    pub seed: Option<u64>,
+
+    /// The objective that is minimized.
+    pub objective: Objective,
+
+    /// The rules to compare moves, the rules of the model are used when no
+    /// rules are given.
+    pub rules: Option<Vec<Rule>>,
+
+    /// The score rules to rank the moves instead of comparing them pairwise
+    /// by the rules, which is disabled by default.
+    pub scoring: Option<Scoring>,
+
+    /// The strategy to choose the point in time the search is restarted from.
+    pub restart: Strategy,
+
+    /// Whether the number of used trains is adapted during the search.
+    pub adapt_trains: bool,
+
+    /// The lookahead for the moves of the trains, which is disabled by
+    /// default.
+    pub rollout: Option<Rollout>,
+
+    /// Whether the fitness of each iteration is tracked, e.g. for plotting.
+    pub track_fitness: bool,
+
+    /// The fitness that is good enough to stop the search.
+    pub target_fitness: Option<TimeDiff>,
+
+    /// The maximum number of compared moves, which stops the search at the
+    /// same point on every machine unlike the duration.
+    pub max_checked_moves: Option<usize>,
+
+    /// The maximum number of restarts.
+    pub max_restarts: Option<usize>,
+
+    /// The number of milli seconds and the minimal relative improvement of
+    /// the best fitness within that time, the search is stopped when it
+    /// improves less.
+    pub stagnation: Option<(u128, f64)>,
+
+    /// The maximum number of repairs of overloaded stations until the search
+    /// is restarted, see [repair](crate::repair::repair).
+    pub max_repairs: usize,
+
+    /// The maximum number of points in time without a passenger boarding or
+    /// detraining until a run is stopped as stuck and the search is
+    /// restarted. By default, runs are not stopped and a run is counted as
+    /// stalled after twice the longest travel time of the slowest train.
+    pub max_stall: Option<Time>,
+
+    /// Whether wasted moves are removed from the best solution when the
+    /// search has finished.
+    pub clean_up: bool,
+
+    /// Whether the rules that decided each chosen move are recorded, see
+    /// [explain](crate::explain::explain).
+    pub explain: bool,
+
+    /// Whether the results of the rules are counted, see
+    /// [Statistics](crate::rule::Statistics).
+    pub rule_statistics: bool,
+
+    /// Whether the deadlocks are detected at each point in time, which the
+    /// rule set `resolve_deadlocks` and the number of deadlocks of the report
+    /// require.
+    pub detect_deadlocks: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            max_millis: 600000,
+            max_iterations: 20000,
+            tabu_size: 8000000,
+            tabu_memory: None,
+            tabu_backend: TabuBackend::Exact,
+            seed: None,
+            objective: Objective::TotalDelay,
+            rules: None,
+            scoring: None,
+            restart: Strategy::Uniform,
+            adapt_trains: false,
+            rollout: None,
+            track_fitness: false,
+            target_fitness: None,
+            max_checked_moves: None,
+            max_restarts: None,
+            stagnation: None,
+            max_repairs: 8,
+            max_stall: None,
+            clean_up: true,
+            explain: false,
+            rule_statistics: false,
+            detect_deadlocks: false,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Sets the maximum number of milli seconds the search should run.
+    pub fn time(mut self, max_millis: u128) -> Self {
+        self.max_millis = max_millis;
+        self
+    }
+
+    /// Sets the maximum number of iterations without improvement.
+    pub fn iterations(mut self, max_iterations: u128) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the maximum number of states in the exact tabu list.
+    pub fn tabu_size(mut self, tabu_size: usize) -> Self {
+        self.tabu_size = tabu_size;
+        self
+    }
+
+    /// Sets the memory budget of the tabu list in bytes.
+    pub fn tabu_memory(mut self, tabu_memory: usize) -> Self {
+        self.tabu_memory = Some(tabu_memory);
+        self
+    }
+
+    /// Sets the data structure of the tabu list.
+    pub fn tabu_backend(mut self, tabu_backend: TabuBackend) -> Self {
+        self.tabu_backend = tabu_backend;
+        self
+    }
+
+    /// Sets the seed of the random generator, which makes searches with the
+    /// same configuration reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the objective that is minimized.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets the rules to compare moves instead of the rules of the model.
+    pub fn rules(mut self, rules: Vec<Rule>) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Ranks the moves of each train by the given score rules instead of
+    /// comparing them pairwise by the rules, see [Scoring].
+    pub fn scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = Some(scoring);
+        self
+    }
+
+    /// Sets the strategy to choose the point in time the search is restarted
+    /// from.
+    pub fn restart(mut self, restart: Strategy) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Adapts the number of used trains during the search: the slowest trains
+    /// are removed as long as the remaining trains reach the best delay, which
+    /// avoids trains blocking each other.
+    pub fn adapt_trains(mut self, adapt_trains: bool) -> Self {
+        self.adapt_trains = adapt_trains;
+        self
+    }
+
+    /// Chooses the move of each train from the best `width` moves by the rules
+    /// by continuing each of them greedily for `depth` points in time, which
+    /// is slower but finds better moves on hard models.
+    pub fn rollout(mut self, width: usize, depth: usize) -> Self {
+        self.rollout = Some(Rollout { width, depth });
+        self
+    }
+
+    /// Tracks the fitness of each iteration.
+    pub fn track_fitness(mut self, track_fitness: bool) -> Self {
+        self.track_fitness = track_fitness;
+        self
+    }
+
+    /// Stops the search as soon as the best fitness is less or equal to the
+    /// given fitness.
+    pub fn target_fitness(mut self, target_fitness: TimeDiff) -> Self {
+        self.target_fitness = Some(target_fitness);
+        self
+    }
+
+    /// Stops the search after the given number of compared moves.
+    pub fn max_checked_moves(mut self, max_checked_moves: usize) -> Self {
+        self.max_checked_moves = Some(max_checked_moves);
+        self
+    }
+
+    /// Stops the search after the given number of restarts.
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+
+    /// Stops the search when the best fitness has improved by less than the
+    /// given fraction, e.g. `0.01`, within the last `window` milli seconds.
+    pub fn stagnation(mut self, window: u128, min_improvement: f64) -> Self {
+        self.stagnation = Some((window, min_improvement));
+        self
+    }
+
+    /// Sets the maximum number of repairs of overloaded stations until the
+    /// search is restarted, `0` restarts the search at every overloaded
+    /// station.
+    pub fn max_repairs(mut self, max_repairs: usize) -> Self {
+        self.max_repairs = max_repairs;
+        self
+    }
+
+    /// Sets the maximum number of points in time without a passenger
+    /// boarding or detraining until a run is stopped as stuck and the search
+    /// is restarted.
+    pub fn max_stall(mut self, max_stall: Time) -> Self {
+        self.max_stall = Some(max_stall);
+        self
+    }
+
+    /// Removes wasted moves from the best solution when the search has
+    /// finished, see [clean_up](crate::cleanup::clean_up).
+    pub fn clean_up(mut self, clean_up: bool) -> Self {
+        self.clean_up = clean_up;
+        self
+    }
+
+    /// Records the rules that decided each chosen move of the best solution
+    /// over the runner-up and over no move.
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Counts how often each rule has returned a result when moves have been
+    /// compared and how often it has decided the chosen move.
+    pub fn rule_statistics(mut self, rule_statistics: bool) -> Self {
+        self.rule_statistics = rule_statistics;
+        self
+    }
+
+    /// Detects the deadlocks once at each point in time instead of on each
+    /// comparison of the rules, see
+    /// [detect_deadlocks](crate::state::State::detect_deadlocks).
+    pub fn detect_deadlocks(mut self, detect_deadlocks: bool) -> Self {
+        self.detect_deadlocks = detect_deadlocks;
+        self
+    }
+
+    /// Constructs the configured tabu list.
+    pub fn tabu_list(&self) -> TabuList {
+        match (self.tabu_backend, self.tabu_memory) {
+            (TabuBackend::Exact, Some(memory)) => TabuList::exact(memory / EXACT_ENTRY_BYTES),
+            (TabuBackend::Exact, None) => TabuList::exact(self.tabu_size),
+            (TabuBackend::Bloom, Some(memory)) => TabuList::bloom(memory),
+            // 16 bits per state keep at least as many states as the exact list
+            (TabuBackend::Bloom, None) => TabuList::bloom(self.tabu_size * 2),
+        }
+    }
+}
+
+/// The reason a search has been terminated.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Termination {
+    /// The fitness of the best solution has reached the lower bound.
+    LowerBound,
+    /// The fitness of the best solution has reached the target fitness.
+    TargetFitness,
+    /// The maximum number of compared moves was exceeded.
+    CheckedMoves,
+    /// The maximum number of restarts was exceeded.
+    Restarts,
+    /// The best fitness has not improved enough within the stagnation window.
+    Stagnation,
+    /// The maximum number of iterations without improvement was exceeded.
+    Iterations,
+    /// The maximum duration was exceeded.
+    Time,
+    /// The process has been interrupted.
+    Interrupted,
+}
+
+impl Termination {
+    /// Gets the name of the termination reason.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Termination::LowerBound => "lower bound reached",
+            Termination::TargetFitness => "target fitness reached",
+            Termination::CheckedMoves => "compared moves limit",
+            Termination::Restarts => "restarts limit",
+            Termination::Stagnation => "stagnation",
+            Termination::Iterations => "iterations without improvement",
+            Termination::Time => "time limit",
+            Termination::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// An improvement of the best solution.
+#[derive(Clone)]
+pub struct Progress {
+    /// The number of iterations since the search has been started.
+    pub iteration: usize,
+
+    /// The fitness of the best solution.
+    pub fitness: TimeDiff,
+
+    /// The number of arrived passengers in the best solution.
+    pub arrived: usize,
+
+    /// The number of milli seconds since the search has been started.
+    pub elapsed: u128,
+}
+
+/// The result of a search.
+pub struct SearchReport {
+    /// The best solution.
+    pub solution: Solution,
+
+    /// The number of milli seconds the search has been running.
+    pub duration: u128,
+
+    /// The number of moves that have been compared.
+    pub checked_moves: usize,
+
+    /// The number of iterations.
+    pub iterations: usize,
+
+    /// The number of restarts.
+    pub restarts: usize,
+
+    /// The number of restarts and improvements for each restart strategy.
+    pub restart_statistics: Vec<(Strategy, Statistics)>,
+
+    /// The improvements of the best solution in chronological order.
+    pub improvements: Vec<Progress>,
+
+    /// The best fitness of all iterations, when the fitness is tracked.
+    pub fitness: Vec<TimeDiff>,
+
+    /// The number of repaired overloaded stations.
+    pub repairs: usize,
+
+    /// The number of detected deadlocks, groups of trains that wait for each
+    /// other in a cycle, `0` unless the deadlocks are detected.
+    pub deadlocks: usize,
+
+    /// The number of runs without a passenger boarding or detraining for too
+    /// long, see [max_stall](SearchConfig::max_stall).
+    pub stalls: usize,
+
+    /// The number of moves that have been removed from the best solution by
+    /// the clean up.
+    pub removed_moves: usize,
+
+    /// The decisions of the moves of the best solution, when they are
+    /// explained.
+    pub decisions: Vec<Decision>,
+
+    /// How often each rule has returned a result and decided a chosen move,
+    /// when the rules are counted.
+    pub rule_statistics: Option<RuleStatistics>,
+
+    /// The estimated false positive rate of the tabu list.
+    pub tabu_false_positive_rate: f64,
+
+    /// The reason the search has been terminated.
+    pub termination: Termination,
+}
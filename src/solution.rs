@@ -1,11 +1,38 @@
+use crate::connection::Id as CId;
 use crate::model::Model;
-use crate::move_::Move;
+use crate::move_::{Board, Depart, Detrain, Move, Start};
+use crate::passenger::{Id as PId, Location as PLocation};
 use crate::state::State;
-use crate::train::{Location as TLocation, Speed};
-use crate::types::{IdSet, TimeDiff};
+use crate::station::Id as SId;
+use crate::train::{Id as TId, Location as TLocation, Speed};
+use crate::types::{IdSet, Time, TimeDiff};
 
 use rust_decimal::Decimal;
 
+/// A move of a timetable in the output format. In contrast to [Move] the
+/// stations a move relates to are not known until the timetable is replayed.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Entry {
+    /// A train starts at a station.
+    Start(TId, SId),
+    /// A train departs via a connection.
+    Depart(TId, CId),
+    /// A passenger boards a train.
+    Board(PId, TId),
+    /// A passenger detrains.
+    Detrain(PId),
+}
+
+/// A part of the journey of a passenger on a single train.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Leg {
+    pub t_id: TId,
+    pub from: SId,
+    pub board: usize,
+    pub to: SId,
+    pub detrain: usize,
+}
+
 /// The soltion holds a list of states at any given point in
 /// time.
 #[derive(Clone)]
@@ -18,11 +45,187 @@ impl Solution {
         Solution(states)
     }
 
+    /// Constructs a solution by replaying the given moves for each point in
+    /// time, starting at the initial state of the model.
+    pub fn replay(model: &Model, moves: &[Vec<Move>]) -> Solution {
+        let mut solution = Solution::new();
+        let mut state = model.initial_state();
+
+        for (t, tick) in moves.iter().enumerate() {
+            if t > 0 {
+                state.next(model);
+            }
+
+            for m in tick {
+                state.push(*m, model);
+            }
+
+            solution.0.push(state.clone());
+        }
+
+        solution
+    }
+
+    /// Constructs a solution by replaying the entries of a timetable for each
+    /// point in time.
+    ///
+    /// The timetable is replayed until an entry is not a legal move or a
+    /// station is overloaded, only the legal prefix is kept.
+    pub fn from_timetable(model: &Model, timetable: &[Vec<Entry>]) -> Solution {
+        let mut solution = Solution::new();
+        let mut state = model.initial_state();
+
+        for (t, entries) in timetable.iter().enumerate() {
+            if t > 0 {
+                state.next(model);
+
+                if !state.is_legal() {
+                    break;
+                }
+            }
+
+            for entry in entries {
+                let m = match *entry {
+                    Entry::Start(t_id, s_id) => Some(Move::Start(Start { t_id, s_id })),
+                    Entry::Depart(t_id, c_id) => match state.t_location[t_id] {
+                        TLocation::Station(from) => Some(Move::Depart(Depart {
+                            t_id,
+                            from,
+                            to: model.get_destination(from, c_id),
+                            c_id,
+                        })),
+                        _ => None,
+                    },
+                    Entry::Board(p_id, t_id) => match state.t_location[t_id] {
+                        TLocation::Station(s_id) => Some(Move::Board(Board { t_id, p_id, s_id })),
+                        _ => None,
+                    },
+                    Entry::Detrain(p_id) => match state.p_location[p_id] {
+                        PLocation::Train(t_id) => match state.t_location[t_id] {
+                            TLocation::Station(s_id) => {
+                                Some(Move::Detrain(Detrain { t_id, p_id, s_id }))
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    },
+                };
+
+                match m {
+                    Some(m) if state.is_legal_move(&m, model) => state.push(m, model),
+                    _ => return solution,
+                }
+            }
+
+            solution.0.push(state.clone());
+        }
+
+        solution
+    }
+
+    /// Gets the moves for each point in time.
+    pub fn moves(&self) -> Vec<Vec<Move>> {
+        self.0.iter().map(|state| state.moves.clone()).collect()
+    }
+
     /// Gets a list of the arrived passengers.
     pub fn arrived_passengers(&self) -> &IdSet {
         &self.0[self.0.len() - 1].p_arrived
     }
 
+    /// Gets the number of trains that have been moved.
+    pub fn used_trains(&self) -> usize {
+        self.0
+            .iter()
+            .flat_map(|state| state.moves.iter())
+            .filter_map(|m| match m {
+                Move::Board(board) => Some(board.t_id),
+                Move::Detrain(detrain) => Some(detrain.t_id),
+                Move::Depart(depart) => Some(depart.t_id),
+                Move::Start(t_start) => Some(t_start.t_id),
+                Move::None(_) => None,
+            })
+            .collect::<IdSet>()
+            .len()
+    }
+
+    /// Gets the legs of the journey of each passenger, a passenger with more
+    /// than one leg transfers between trains. A leg without a detrain ends at
+    /// the last point in time.
+    pub fn journeys(&self, model: &Model) -> Vec<Vec<Leg>> {
+        let mut journeys: Vec<Vec<Leg>> = vec![vec![]; model.passengers.len()];
+
+        for (t, state) in self.0.iter().enumerate() {
+            for m in &state.moves {
+                match m {
+                    Move::Board(board) => journeys[board.p_id].push(Leg {
+                        t_id: board.t_id,
+                        from: board.s_id,
+                        board: t,
+                        to: board.s_id,
+                        detrain: self.0.len() - 1,
+                    }),
+                    Move::Detrain(detrain) => {
+                        if let Some(leg) = journeys[detrain.p_id].last_mut() {
+                            leg.to = detrain.s_id;
+                            leg.detrain = t;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        journeys
+    }
+
+    /// Gets the number of transfers between trains of all passengers.
+    pub fn transfers(&self, model: &Model) -> usize {
+        self.journeys(model)
+            .iter()
+            .map(|legs| legs.len().saturating_sub(1))
+            .sum()
+    }
+
+    /// Gets the deadlocks of the solution: the groups of trains that block
+    /// each other for more than one point in time, see
+    /// [State::deadlocks]. Each deadlock is given by its first and last point
+    /// in time.
+    pub fn deadlocks(&self, model: &Model) -> Vec<(Time, Time, Vec<TId>)> {
+        let mut deadlocks: Vec<(Time, Time, Vec<TId>)> = vec![];
+        let mut holding: Vec<(Time, Vec<TId>)> = vec![];
+
+        for (t, state) in self.0.iter().enumerate() {
+            let detected = state.deadlocks(model);
+            let (held, ended): (Vec<_>, Vec<_>) = holding
+                .into_iter()
+                .partition(|(_, group)| detected.contains(group));
+
+            for (since, group) in ended {
+                if since + 1 < t {
+                    deadlocks.push((since, t - 1, group));
+                }
+            }
+
+            holding = detected
+                .into_iter()
+                .map(|group| match held.iter().find(|(_, g)| *g == group) {
+                    Some(&(since, _)) => (since, group),
+                    None => (t, group),
+                })
+                .collect();
+        }
+
+        for (since, group) in holding {
+            if since + 1 < self.0.len() {
+                deadlocks.push((since, self.0.len() - 1, group));
+            }
+        }
+
+        deadlocks.sort();
+        deadlocks
+    }
+
     /// Gets a list of delays for each passenger.
     pub fn delays(&self) -> Vec<i32> {
         self.0[self.0.len() - 1].p_delays.clone()
@@ -54,6 +257,14 @@ impl Solution {
             .sum::<TimeDiff>()
     }
 
+    /// Gets the maximum delay of all passengers of the solution.
+    pub fn max_delay(&self) -> TimeDiff {
+        match self.0.last() {
+            Some(state) => state.p_delays.iter().copied().max().unwrap_or(0).max(0),
+            None => TimeDiff::MAX,
+        }
+    }
+
     fn to_string_verbose(&self, model: &Model) -> String {
         let mut string: String = "".to_owned();
 
@@ -1,3 +1,4 @@
+use crate::connection::Id as CId;
 use crate::model::Model;
 use crate::move_::{Board, Depart, Detrain, Move, Start};
 use crate::passenger::Location as PLocation;
@@ -44,6 +45,11 @@ pub struct State {
 
     /// A vector containing moves that have been pushed to this state.
     pub moves: Vec<Move>,
+
+    /// The groups of trains that block each other at the beginning of the
+    /// point in time, `None` when they have not been detected, see
+    /// [detect_deadlocks](State::detect_deadlocks).
+    pub deadlocked: Option<Vec<Vec<TId>>>,
 }
 
 impl State {
@@ -86,6 +92,7 @@ impl State {
             p_arrived: IdSet::default(),
             moves: vec![],
             p_delays,
+            deadlocked: None,
         }
     }
 
@@ -134,6 +141,7 @@ impl State {
     pub fn next(&mut self, model: &Model) {
         self.t += 1;
         self.moves = vec![];
+        self.deadlocked = None;
 
         // check for arrived trains
         for (t_id, location) in self.t_location.clone().iter().enumerate() {
@@ -183,6 +191,27 @@ impl State {
         moves
     }
 
+    /// Determines whether the given move can be pushed to the state, which is
+    /// the case when it is a move of the train and neither the train nor the
+    /// passenger has moved at this point in time.
+    pub fn is_legal_move(&self, m: &Move, model: &Model) -> bool {
+        let t_id = match m {
+            Move::Board(board) => board.t_id,
+            Move::Detrain(detrain) => detrain.t_id,
+            Move::Depart(depart) => depart.t_id,
+            Move::Start(t_start) => t_start.t_id,
+            Move::None(_) => return true,
+        };
+
+        let p_moved = match m {
+            Move::Board(board) => self.passenger_move(board.p_id).is_some(),
+            Move::Detrain(detrain) => self.passenger_move(detrain.p_id).is_some(),
+            _ => false,
+        };
+
+        self.train_move(t_id).is_none() && !p_moved && self.get_moves(t_id, model).contains(m)
+    }
+
     /// Gets a vector of boarding moves for the given train and station.
     fn boardings(&self, t_id: TId, s_id: SId, model: &Model) -> Vec<Move> {
         self.s_passengers[s_id]
@@ -265,7 +294,9 @@ impl State {
                     self.p_delays[detrain.p_id] =
                         self.t as i32 - model.passengers[detrain.p_id].arrival as i32;
                 } else {
+                    // the passenger waits for another train
                     self.p_location[detrain.p_id] = PLocation::Station(detrain.s_id);
+                    self.s_passengers[detrain.s_id].insert(detrain.p_id);
                 }
             }
             Move::Depart(depart) => {
@@ -300,6 +331,8 @@ impl State {
                     if detrain.s_id == model.passengers[detrain.p_id].destination {
                         self.p_arrived.remove(&detrain.p_id);
                         self.p_delays[detrain.p_id] = model.t_max as TimeDiff;
+                    } else {
+                        self.s_passengers[detrain.s_id].remove(&detrain.p_id);
                     }
                 }
                 Move::Depart(depart) => {
@@ -320,6 +353,237 @@ impl State {
         None
     }
 
+    /// Determines whether a passenger has boarded or detrained at this point
+    /// in time, a search without such progress for many points in time is
+    /// stuck in a loop.
+    pub fn has_progress(&self) -> bool {
+        self.moves
+            .iter()
+            .any(|m| matches!(m, Move::Board(_) | Move::Detrain(_)))
+    }
+
+    /// Gets the next station on the travel path of the most urgent passenger
+    /// of the train, `None` for empty trains and trains that are not located
+    /// at a station.
+    pub fn next_station(&self, t_id: TId, model: &Model) -> Option<SId> {
+        let s_id = match self.t_location[t_id] {
+            TLocation::Station(s_id) => s_id,
+            _ => return None,
+        };
+        let p_id = *self.t_passengers[t_id]
+            .iter()
+            .min_by_key(|&&p_id| (model.passengers[p_id].arrival, p_id))?;
+
+        model
+            .paths
+            .get(&(s_id, model.passengers[p_id].destination))?
+            .path
+            .get(1)
+            .copied()
+    }
+
+    /// Gets the trains the given train waits for. The train waits when all
+    /// neighbouring stations that are closer to the destination of its most
+    /// urgent passenger are blocked: by the trains located at a full station
+    /// or by the trains on the connections to the station when all of them
+    /// are full. Trains that still detrain passengers at their station do not
+    /// wait.
+    pub fn waits_for(&self, t_id: TId, model: &Model) -> Vec<TId> {
+        let s_id = match self.t_location[t_id] {
+            TLocation::Station(s_id) => s_id,
+            _ => return vec![],
+        };
+
+        if self.t_passengers[t_id]
+            .iter()
+            .any(|&p_id| model.passengers[p_id].destination == s_id)
+        {
+            return vec![];
+        }
+
+        let destination = match self.t_passengers[t_id]
+            .iter()
+            .min_by_key(|&&p_id| (model.passengers[p_id].arrival, p_id))
+        {
+            Some(&p_id) => model.passengers[p_id].destination,
+            None => return vec![],
+        };
+        let distance = |s_id: SId| match s_id == destination {
+            true => Decimal::ZERO,
+            false => model.distance(s_id, destination),
+        };
+        let mut neighbours: Vec<SId> = model.station_connections[s_id]
+            .iter()
+            .map(|&c_id| model.get_destination(s_id, c_id))
+            .filter(|&next| distance(next) < distance(s_id))
+            .collect();
+        let mut waits = vec![];
+
+        neighbours.sort_unstable();
+        neighbours.dedup();
+
+        for next in neighbours {
+            let connections: Vec<CId> = model.station_connections[s_id]
+                .iter()
+                .copied()
+                .filter(|&c_id| model.get_destination(s_id, c_id) == next)
+                .collect();
+            let station_full = self.s_capacity[next] <= 0;
+            let connections_full = connections.iter().all(|&c_id| self.c_capacity[c_id] <= 0);
+
+            if !station_full && !connections_full {
+                return vec![];
+            }
+
+            waits.extend(
+                self.t_location
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, location)| {
+                        other != t_id
+                            && match *location {
+                                TLocation::Station(at) => station_full && at == next,
+                                TLocation::Connection(c_id, _, _) => {
+                                    connections_full && connections.contains(&c_id)
+                                }
+                                TLocation::Nothing => false,
+                            }
+                    })
+                    .map(|(other, _)| other),
+            );
+        }
+
+        waits.sort_unstable();
+        waits.dedup();
+        waits
+    }
+
+    /// Determines whether the train is parked: an empty train at a station,
+    /// which blocks the station until a rule sends it somewhere else.
+    pub fn is_parked(&self, t_id: TId) -> bool {
+        matches!(self.t_location[t_id], TLocation::Station(_)) && self.t_passengers[t_id].is_empty()
+    }
+
+    /// Determines whether the train is too late to bring its most urgent
+    /// passenger to the destination in time, even when departing now.
+    fn is_late(&self, t_id: TId, model: &Model) -> bool {
+        let s_id = match self.t_location[t_id] {
+            TLocation::Station(s_id) => s_id,
+            _ => return false,
+        };
+
+        self.t_passengers[t_id]
+            .iter()
+            .min_by_key(|&&p_id| (model.passengers[p_id].arrival, p_id))
+            .map_or(false, |&p_id| {
+                let passenger = &model.passengers[p_id];
+
+                self.t
+                    .saturating_add(model.travel_time(t_id, s_id, passenger.destination))
+                    > passenger.arrival
+            })
+    }
+
+    /// Gets the groups of trains that block each other, e.g. two trains at
+    /// full stations that want to go to the station of the other train, or a
+    /// late train that waits for a parked train. A train is blocked when all
+    /// the trains it waits for are blocked, or parked while the train is late,
+    /// trains that wait for a train that moves on are not. Each group consists
+    /// of the blocked trains and the parked trains they wait for, sorted by the
+    /// train ids.
+    ///
+    /// A single state cannot tell a deadlock from a short wait, hence a group
+    /// should only be reported as deadlock when it holds for more than one
+    /// point in time.
+    pub fn deadlocks(&self, model: &Model) -> Vec<Vec<TId>> {
+        let waits: Vec<Vec<TId>> = (0..self.t_location.len())
+            .map(|t_id| self.waits_for(t_id, model))
+            .collect();
+        let late: Vec<bool> = (0..waits.len())
+            .map(|t_id| self.is_late(t_id, model))
+            .collect();
+        let mut blocked: Vec<bool> = waits.iter().map(|w| !w.is_empty()).collect();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for t_id in 0..waits.len() {
+                if blocked[t_id]
+                    && waits[t_id]
+                        .iter()
+                        .any(|&other| match self.is_parked(other) {
+                            true => !late[t_id],
+                            false => !blocked[other],
+                        })
+                {
+                    blocked[t_id] = false;
+                    changed = true;
+                }
+            }
+        }
+
+        // the blocked trains connected by their waits form a group
+        let mut group_of: Vec<Option<usize>> = vec![None; waits.len()];
+        let mut deadlocks: Vec<Vec<TId>> = vec![];
+
+        for t_id in (0..waits.len()).filter(|&t_id| blocked[t_id]) {
+            let mut stack = vec![t_id];
+            let mut group = vec![];
+
+            if group_of[t_id].is_some() {
+                continue;
+            }
+
+            while let Some(t_id) = stack.pop() {
+                if group_of[t_id].is_some() {
+                    continue;
+                }
+
+                group_of[t_id] = Some(deadlocks.len());
+                group.push(t_id);
+
+                if blocked[t_id] {
+                    stack.extend(waits[t_id].iter().copied());
+                }
+
+                // the blocked trains that wait for this train
+                stack.extend(
+                    (0..waits.len())
+                        .filter(|&other| blocked[other] && waits[other].contains(&t_id)),
+                );
+            }
+
+            group.sort_unstable();
+            deadlocks.push(group);
+        }
+
+        deadlocks
+    }
+
+    /// Detects the deadlocks once per point in time, see
+    /// [deadlocks](State::deadlocks). The moves pushed afterwards are not
+    /// taken into account.
+    pub fn detect_deadlocks(&mut self, model: &Model) -> &[Vec<TId>] {
+        if self.deadlocked.is_none() {
+            self.deadlocked = Some(self.deadlocks(model));
+        }
+
+        self.deadlocked.as_deref().unwrap_or_default()
+    }
+
+    /// Determines whether the train belongs to a group of trains that block
+    /// each other, see [deadlocks](State::deadlocks). The detected deadlocks
+    /// are used when they have been detected at this point in time.
+    pub fn is_deadlocked(&self, t_id: TId, model: &Model) -> bool {
+        let contains = |deadlocks: &[Vec<TId>]| deadlocks.iter().any(|group| group.contains(&t_id));
+
+        match &self.deadlocked {
+            Some(deadlocks) => contains(deadlocks),
+            None => contains(&self.deadlocks(model)),
+        }
+    }
+
     /// Gets the move for the given train.
     pub fn train_move(&self, t_id: TId) -> Option<&Move> {
         self.moves.iter().find(|m| match m {
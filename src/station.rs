@@ -3,7 +3,7 @@ use crate::types;
 pub type Id = types::Id;
 pub type Capacity = types::Capacity;
 
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 pub struct Station {
     pub name: String,
     pub capacity: Capacity,
@@ -1,100 +1,368 @@
+use crate::checkpoint::{Adaptation, Checkpoint};
+use crate::cleanup::clean_up;
+use crate::explain::Decision;
+use crate::interrupt::is_interrupted;
 use crate::model::Model;
 use crate::move_::{Move, None};
+use crate::repair::repair;
+use crate::restart::{Statistics, Strategy, STRATEGIES};
+use crate::rollout::rank;
+use crate::rule::{Rule, Statistics as RuleStatistics};
+use crate::search::{Progress, SearchConfig, SearchReport, Termination};
 use crate::solution::Solution;
 use crate::state::State;
-use crate::types::TimeDiff;
-use fxhash::hash32;
-use fxhash::FxBuildHasher;
-use linked_hash_set::LinkedHashSet;
+use crate::tabu_list::TabuList;
+use crate::train::Id as TId;
+use crate::types::{Time, TimeDiff};
+use fxhash::hash64;
 use rand::seq::SliceRandom;
-use rand::Rng;
-use std::time::Instant;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::time::{Duration, Instant};
+
+/// The number of restarts after which the number of used trains is reduced
+/// when the search stagnates or the trains are blocking each other.
+const ADAPT_RESTARTS: usize = 64;
+
+/// Gets twice the longest travel time of the slowest train between two
+/// stations, a run without a passenger boarding or detraining for longer is
+/// stuck.
+fn default_max_stall(model: &Model) -> Time {
+    let slowest = (0..model.trains.len()).min_by_key(|&t_id| model.trains[t_id].speed);
+
+    slowest.map_or(Time::MAX, |t_id| {
+        model
+            .paths
+            .keys()
+            .map(|&(a, b)| model.travel_time(t_id, a, b))
+            .filter(|&time| time < Time::MAX)
+            .max()
+            .unwrap_or(0)
+            .saturating_mul(2)
+            .max(1)
+    })
+}
+
+/// A closure that observes the progress of a search, it is called whenever
+/// the best solution has been improved.
+pub type Observer = Box<dyn FnMut(&Progress)>;
 
 /// Tabu-enhanced genetic search.
 pub struct TabuGeneticSearch {
-    /// A HashSet that holds states that have been visited before. States are
-    /// popped when the maximum number of states have been added to the set.
-    tabu: LinkedHashSet<u32, FxBuildHasher>,
+    /// The configuration of the search.
+    config: SearchConfig,
+
+    /// Holds hashes of states that have been visited before. The oldest
+    /// states are evicted when the tabu list is full.
+    tabu: TabuList,
+
+    /// A closure that is called with the progress of the search.
+    observer: Option<Observer>,
 
-    /// The maximum number of milli seconds the algorithm list should run.
-    max_millis: u128,
+    /// The random generator, its state is stored in checkpoints.
+    rnd: ChaCha8Rng,
 
-    /// The maximum number of iterations without improvement.
-    max_iterations: u128,
+    /// The path of the checkpoint file and the number of milli seconds
+    /// between two checkpoints.
+    checkpoint: Option<(String, u128)>,
 
-    /// The maxmimum number of items in the tabu list.
-    tabu_size: usize,
+    /// The checkpoint the search is resumed from.
+    resume: Option<Checkpoint>,
 
-    /// Wether to track fitness or not.
-    track_fitness: bool,
+    /// The solution the search is started from.
+    warm_start: Option<Solution>,
 
-    /// A vector containing the best sum of delays fo all iterations.
-    pub fitness: Vec<TimeDiff>,
+    /// The number of trains that are moved, the fastest trains are used first.
+    trains: usize,
+
+    /// The number of restarts and improvements for each restart strategy.
+    restart_statistics: Vec<(Strategy, Statistics)>,
+
+    /// The improvements of the best solution.
+    improvements: Vec<Progress>,
+
+    /// A vector containing the best fitness of all iterations.
+    fitness: Vec<TimeDiff>,
 
     /// The number of moves that have been checked.
-    pub checked_moves: usize,
+    checked_moves: usize,
+
+    /// The decisions of the moves of the current solution, when they are
+    /// explained.
+    decisions: Vec<Decision>,
+
+    /// How often each rule has returned a result and decided a chosen move,
+    /// when the rules are counted.
+    rule_statistics: Option<RuleStatistics>,
+}
+
+/// The solutions and counters of a search, which are carried from one run to
+/// the next.
+struct Search {
+    /// The point in time the search has been started, a resumed search has
+    /// started the elapsed time of the checkpoint earlier.
+    start_time: Instant,
+
+    /// The point in time the last checkpoint has been written.
+    checkpoint_time: Instant,
+
+    /// The current solution.
+    solution: Solution,
+
+    /// The current state.
+    state: State,
+
+    /// The best solution.
+    best_solution: Solution,
+
+    /// The decisions of the moves of the best solution.
+    best_decisions: Vec<Decision>,
+
+    /// The minimal delay of all iterations since the last restart without
+    /// the slowest train.
+    min_delay: TimeDiff,
+
+    /// The number of iterations without improvement.
+    no_improvements: u128,
+
+    /// The number of iterations.
+    iterations: usize,
+
+    /// The number of restarts.
+    restarts: usize,
+
+    /// The number of repaired overloaded stations.
+    repairs: usize,
+
+    /// The number of detected deadlocks.
+    deadlocks: usize,
+
+    /// The number of stalled runs.
+    stalls: usize,
+
+    /// The strategy that has chosen the last restart point.
+    restarted_by: Option<Strategy>,
+
+    /// The number of points in time without a passenger boarding or
+    /// detraining after which a run is stalled.
+    max_stall: Time,
+
+    /// Whether the number of used trains is adapted.
+    adapting: bool,
+
+    /// The number of restarts of the current number of used trains.
+    phase_restarts: usize,
+
+    /// The number of illegal solutions of the current number of used trains.
+    phase_illegal: usize,
+
+    /// Whether the current number of used trains has improved its best
+    /// solution.
+    phase_improved: bool,
+
+    /// The best delay that has been reached for each number of used trains.
+    best_by_trains: Vec<TimeDiff>,
+
+    /// The best solution for the current number of used trains.
+    phase_best: Solution,
+}
+
+/// The counters of a single run from the restart point on.
+#[derive(Default)]
+struct Run {
+    /// The number of repaired overloaded stations.
+    repairs: usize,
+
+    /// The groups of blocked trains of the previous point in time.
+    blocked: Vec<Vec<TId>>,
+
+    /// The deadlocks that have been counted and still hold.
+    deadlocks: Vec<Vec<TId>>,
+
+    /// The last point in time a passenger has been moved.
+    progress_t: Time,
+
+    /// Whether the run has been counted as stalled.
+    stalled: bool,
 }
 
 impl TabuGeneticSearch {
     /// Constructs a new TabuGeneticSearch struct.
-    pub fn new(
-        max_millis: u128,
-        max_iterations: u128,
-        tabu_size: usize,
-        track_fitness: bool,
-    ) -> TabuGeneticSearch {
+    pub fn new(config: SearchConfig) -> TabuGeneticSearch {
         TabuGeneticSearch {
-            tabu: LinkedHashSet::<u32, FxBuildHasher>::default(),
+            tabu: config.tabu_list(),
+            rnd: match config.seed {
+                Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+                Option::None => ChaCha8Rng::from_entropy(),
+            },
+            config,
+            observer: Option::None,
+            checkpoint: Option::None,
+            resume: Option::None,
+            warm_start: Option::None,
+            trains: 0,
+            restart_statistics: vec![],
+            improvements: vec![],
             fitness: vec![],
-            max_millis,
-            max_iterations,
-            tabu_size,
-            track_fitness,
             checked_moves: 0,
+            decisions: vec![],
+            rule_statistics: Option::None,
         }
     }
 
-    /// Find the best neighbour for the given state
-    fn find_neighbour(&mut self, state: &mut State, model: &Model) {
-        let mut rnd = rand::thread_rng();
+    /// Gets the configuration of the search.
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    /// Writes a checkpoint to the given path every `interval` milli seconds
+    /// and when the search is stopped before it has finished.
+    pub fn checkpoint(&mut self, path: &str, interval: u128) {
+        self.checkpoint = Some((path.to_string(), interval));
+    }
+
+    /// Resumes the search from the given checkpoint, the checkpoint must have
+    /// been written for the same model.
+    pub fn resume(&mut self, checkpoint: Checkpoint) {
+        self.rnd = ChaCha8Rng::from_seed(checkpoint.seed);
+        self.rnd.set_stream(checkpoint.stream);
+        self.rnd.set_word_pos(checkpoint.word_pos);
+        checkpoint
+            .tabu
+            .iter()
+            .for_each(|&hash| self.tabu.insert(hash));
+        self.resume = Some(checkpoint);
+    }
+
+    /// Sets the observer that is called whenever the best solution has been
+    /// improved.
+    pub fn observe<F: FnMut(&Progress) + 'static>(&mut self, observer: F) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Starts the search from the given solution instead of the initial state
+    /// of the model, e.g. a timetable of a previous search.
+    pub fn warm_start(&mut self, solution: Solution) {
+        self.warm_start = Some(solution);
+    }
+
+    /// Gets the statistics of the given restart strategy.
+    fn statistics(&mut self, strategy: Strategy) -> &mut Statistics {
+        &mut self
+            .restart_statistics
+            .iter_mut()
+            .find(|(s, _)| *s == strategy)
+            .expect("Unknown restart strategy")
+            .1
+    }
+
+    /// Determines whether the maximum number of compared moves is exceeded.
+    fn exceeds_checked_moves(&self) -> bool {
+        self.config
+            .max_checked_moves
+            .map_or(false, |max| self.checked_moves >= max)
+    }
+
+    /// Determines whether the best fitness has improved less than required
+    /// within the stagnation window until the given point in time.
+    fn is_stagnating(&self, fitness: TimeDiff, elapsed: u128) -> bool {
+        let (window, min_improvement) = match self.config.stagnation {
+            Some(stagnation) => stagnation,
+            Option::None => return false,
+        };
+
+        if elapsed < window {
+            return false;
+        }
 
+        // the best fitness at the beginning of the window
+        match self
+            .improvements
+            .iter()
+            .rev()
+            .find(|progress| progress.elapsed <= elapsed - window)
+        {
+            Some(progress) => {
+                (progress.fitness - fitness) as f64 <= progress.fitness as f64 * min_improvement
+            }
+            Option::None => false,
+        }
+    }
+
+    /// Find the best neighbour for the given state
+    fn find_neighbour(&mut self, state: &mut State, rules: &[Rule], model: &Model) {
         // list of possible states
         let mut moves: Vec<Move> = vec![];
 
         let move_none = Move::None(None());
 
-        // the best move
-        let mut best_move: Move = move_none;
-
-        for t_id in 0..(model.used_trains) {
+        for t_id in 0..self.trains {
             moves = state.get_moves(t_id, model);
 
             if moves.is_empty() {
                 continue;
             }
 
-            best_move = move_none;
-
             // shuffling the moves somehow leads to finding good solutions much
             // faster...
-            moves.shuffle(&mut rnd);
+            moves.shuffle(&mut self.rnd);
 
-            // find neighbour with best cost that is not tabu
-            for m in moves.into_iter() {
-                self.checked_moves += 1;
+            // the best move
+            let best_move = if let Some(rollout) = self.config.rollout {
+                // candidates that are better than no move and not tabu
+                let candidates: Vec<Move> = moves
+                    .into_iter()
+                    .filter(|&m| {
+                        self.checked_moves += 1;
 
-                if !m.is_gt(&best_move, state, model) || !m.is_gt(&move_none, state, model) {
-                    continue;
-                }
+                        if !m.is_gt_with(&move_none, rules, state, model) {
+                            return false;
+                        }
 
-                state.push(m, model);
+                        state.push(m, model);
+                        let tabu = self.tabu.contains(hash64(state));
+                        state.pop(model);
 
-                if !self.tabu.contains(&hash32(state)) {
-                    best_move = m;
-                }
+                        !tabu
+                    })
+                    .collect();
+                let candidates = rank(
+                    candidates,
+                    rollout.width,
+                    rules,
+                    state,
+                    model,
+                    &mut self.checked_moves,
+                );
 
-                state.pop(model);
-            }
+                let (m, checked_moves) =
+                    rollout.choose(state, t_id, &candidates, self.trains, rules, model);
+
+                self.checked_moves += checked_moves;
+                m.unwrap_or(move_none)
+            } else if let Some(scoring) = &self.config.scoring {
+                self.checked_moves += moves.len();
+
+                // the best ranked move that is not tabu
+                scoring
+                    .rank(moves, state, model)
+                    .into_iter()
+                    .find(|&m| {
+                        state.push(m, model);
+                        let tabu = self.tabu.contains(hash64(state));
+                        state.pop(model);
+
+                        !tabu
+                    })
+                    .unwrap_or(move_none)
+            } else if self.config.explain {
+                let best_move = self.best_move(moves.clone(), state, rules, model);
+
+                self.explain(best_move, moves, state, rules, model);
+                best_move
+            } else {
+                self.best_move(moves, state, rules, model)
+            };
 
             if let Move::None(_) = best_move {
             } else {
@@ -106,92 +374,734 @@ impl TabuGeneticSearch {
         }
     }
 
+    /// Gets the best move by the rules that is not tabu.
+    fn best_move(
+        &mut self,
+        moves: Vec<Move>,
+        state: &mut State,
+        rules: &[Rule],
+        model: &Model,
+    ) -> Move {
+        let move_none = Move::None(None());
+        let mut best_move = move_none;
+        let mut deciding_rule = Option::None;
+
+        // find neighbour with best cost that is not tabu
+        for m in moves.into_iter() {
+            self.checked_moves += 1;
+
+            let decision = self.deciding_rule(&m, &best_move, rules, state, model);
+
+            if !decision.map_or(false, |(_, result)| result)
+                || !self
+                    .deciding_rule(&m, &move_none, rules, state, model)
+                    .map_or(false, |(_, result)| result)
+            {
+                continue;
+            }
+
+            state.push(m, model);
+
+            if !self.tabu.contains(hash64(state)) {
+                best_move = m;
+                deciding_rule = decision;
+            }
+
+            state.pop(model);
+        }
+
+        if let (Some(statistics), Some((i, _))) = (&mut self.rule_statistics, deciding_rule) {
+            statistics.decided[i] += 1;
+        }
+
+        best_move
+    }
+
+    /// Gets the rule that decides whether the move is greater than the other
+    /// move, the decision is counted when the rules are counted.
+    fn deciding_rule(
+        &mut self,
+        a: &Move,
+        b: &Move,
+        rules: &[Rule],
+        state: &State,
+        model: &Model,
+    ) -> Option<(usize, bool)> {
+        let decision = a.deciding_rule(b, rules, state, model);
+
+        if let Some(statistics) = &mut self.rule_statistics {
+            statistics.record(decision);
+        }
+
+        decision
+    }
+
+    /// Records the rules that decided the chosen move over the runner-up,
+    /// which is the best of the other moves, and over no move.
+    fn explain(
+        &mut self,
+        chosen: Move,
+        moves: Vec<Move>,
+        state: &mut State,
+        rules: &[Rule],
+        model: &Model,
+    ) {
+        if let Move::None(_) = chosen {
+            return;
+        }
+
+        // the runner-up is neither counted as compared moves nor in the rule
+        // statistics
+        let checked_moves = self.checked_moves;
+        let rule_statistics = self.rule_statistics.take();
+        let others: Vec<Move> = moves.into_iter().filter(|&m| m != chosen).collect();
+        let runner_up = self.best_move(others, state, rules, model);
+
+        self.checked_moves = checked_moves;
+        self.rule_statistics = rule_statistics;
+
+        let passengers = match chosen {
+            Move::Board(board) => vec![board.p_id],
+            Move::Detrain(detrain) => vec![detrain.p_id],
+            Move::Depart(depart) => state.t_passengers[depart.t_id].iter().copied().collect(),
+            _ => vec![],
+        };
+
+        self.decisions.push(Decision {
+            t: state.t,
+            chosen,
+            runner_up,
+            over_runner_up: match runner_up {
+                Move::None(_) => Option::None,
+                _ => chosen.deciding_rule(&runner_up, rules, state, model),
+            },
+            over_none: chosen.deciding_rule(&Move::None(None()), rules, state, model),
+            passengers,
+        });
+    }
+
     /// Add state to tabu list
     fn add_to_tabu_list(&mut self, state: &State) {
-        self.tabu.insert(hash32(state));
+        self.tabu.insert(hash64(state));
+    }
+
+    /// Runs the search and raises `t_max` of the model by `step` as long as
+    /// passengers have not arrived until `t_max` and the ceiling has not been
+    /// reached. Each search is started from the best solution of the previous
+    /// one, the limits of the duration, compared moves and restarts apply to
+    /// all searches.
+    pub fn search_escalating(
+        &mut self,
+        model: &mut Model,
+        step: Time,
+        ceiling: Time,
+    ) -> SearchReport {
+        let start_time = Instant::now();
+        let max_millis = self.config.max_millis;
+        let max_checked_moves = self.config.max_checked_moves;
+        let max_restarts = self.config.max_restarts;
+        let mut report = self.search(model);
+
+        while report.solution.arrived_passengers().len() < model.passengers.len()
+            && model.t_max < ceiling
+            && start_time.elapsed().as_millis() < max_millis
+            && !is_interrupted()
+            && report.termination != Termination::CheckedMoves
+            && report.termination != Termination::Restarts
+        {
+            // time passes after the check above, hence the remaining time
+            // may already be used up
+            let remaining_millis = max_millis.saturating_sub(start_time.elapsed().as_millis());
+
+            if remaining_millis == 0 {
+                report.termination = Termination::Time;
+                break;
+            }
+
+            let t_max = (model.t_max + step.max(1)).min(ceiling);
+
+            eprintln!(
+                "[t-max] {}/{} passengers arrived until {}, raising t-max to {}",
+                report.solution.arrived_passengers().len(),
+                model.passengers.len(),
+                model.t_max,
+                t_max
+            );
 
-        if self.tabu.len() > self.tabu_size {
-            self.tabu.pop_back();
+            // the delays of passengers that have not arrived depend on t_max,
+            // hence the solution is replayed with the new t_max
+            model.t_max = t_max;
+            self.config.max_millis = remaining_millis;
+            self.config.max_checked_moves =
+                max_checked_moves.map(|max| max.saturating_sub(report.checked_moves));
+            self.config.max_restarts = max_restarts.map(|max| max.saturating_sub(report.restarts));
+            self.warm_start(Solution::replay(model, &report.solution.moves()));
+
+            let next = self.search(model);
+
+            // the counters cover all searches
+            report = SearchReport {
+                checked_moves: report.checked_moves + next.checked_moves,
+                iterations: report.iterations + next.iterations,
+                restarts: report.restarts + next.restarts,
+                repairs: report.repairs + next.repairs,
+                deadlocks: report.deadlocks + next.deadlocks,
+                stalls: report.stalls + next.stalls,
+                removed_moves: report.removed_moves + next.removed_moves,
+                // the decisions of the warm start are kept unless they have
+                // been made again
+                decisions: report
+                    .decisions
+                    .into_iter()
+                    .filter(|d| {
+                        d.is_in(&next.solution)
+                            && !next
+                                .decisions
+                                .iter()
+                                .any(|n| n.t == d.t && n.chosen == d.chosen)
+                    })
+                    .chain(next.decisions.iter().cloned())
+                    .collect(),
+                rule_statistics: match (report.rule_statistics, &next.rule_statistics) {
+                    (Some(mut statistics), Some(next)) => {
+                        statistics.add(next);
+                        Some(statistics)
+                    }
+                    (statistics, _) => statistics,
+                },
+                restart_statistics: report
+                    .restart_statistics
+                    .into_iter()
+                    .zip(next.restart_statistics.iter())
+                    .map(|((strategy, a), (_, b))| {
+                        (
+                            strategy,
+                            Statistics {
+                                restarts: a.restarts + b.restarts,
+                                improvements: a.improvements + b.improvements,
+                            },
+                        )
+                    })
+                    .collect(),
+                improvements: [report.improvements, next.improvements].concat(),
+                fitness: [report.fitness, next.fitness].concat(),
+                ..next
+            };
         }
+
+        self.config.max_millis = max_millis;
+        self.config.max_checked_moves = max_checked_moves;
+        self.config.max_restarts = max_restarts;
+        report.duration = start_time.elapsed().as_millis();
+
+        report
     }
 
-    pub fn search(&mut self, model: &Model) -> (Solution, u128) {
-        // random generator
-        let mut rnd = rand::thread_rng();
+    /// Runs the search on the given model and reports the best solution.
+    pub fn search(&mut self, model: &Model) -> SearchReport {
+        let objective = self.config.objective;
 
-        // start system time
-        let start_time = Instant::now();
+        // the rules of the configuration are used instead of the rules of the
+        // model, they are put back when the search has finished
+        let config_rules = self.config.rules.take();
+        let rules = config_rules.as_deref().unwrap_or(&model.rules);
 
-        // the current solution
-        let mut solution: Solution = Solution::new();
+        let mut search = self.start(rules, model);
+        let mut termination = Termination::LowerBound;
 
-        // the best solution
-        let mut best_solution: Solution = Solution::new();
-        let mut min_delay = TimeDiff::MAX;
+        // the search can be stopped as soon as the lower bound or the target
+        // fitness is reached
+        let lower_bound = objective.lower_bound(model);
+        let goal = lower_bound.max(self.config.target_fitness.unwrap_or(lower_bound));
 
-        // the current state
-        let mut state: State = model.initial_state();
+        while objective.fitness(&search.best_solution) > goal
+            || !search.best_solution.is_legal()
+            || search.adapting
+        {
+            self.run(&mut search, rules, model);
+            self.keep_best(&mut search);
 
-        // The start time for the next iteration.
-        let mut start: usize = 0;
+            let reduced = self.adapt_trains(&mut search, model);
 
-        let mut no_improvements = 0;
+            // the restart point is chosen before the termination by
+            // iterations, which keeps the random generator of checkpoints
+            let start = self.restart_point(&mut search, reduced, model);
 
-        let mut illegal = 0;
+            if search.no_improvements > self.config.max_iterations {
+                termination = Termination::Iterations;
+                break;
+            }
 
-        while best_solution.fitness() > 0 || !best_solution.is_legal() {
-            while state.t <= model.t_max {
-                self.find_neighbour(&mut state, model);
-                solution.0.push(state.clone());
+            self.restart(&mut search, start, model);
 
-                if solution.fitness() < min_delay {
-                    min_delay = solution.fitness();
-                    no_improvements = 0;
-                } else {
-                    no_improvements += 1;
-                }
+            let stop = self.stop(&search);
 
-                if self.track_fitness {
-                    self.fitness.push(min_delay);
-                }
+            self.write_checkpoint(&mut search, stop.is_some(), model);
+
+            if let Some(reason) = stop {
+                termination = reason;
+                break;
+            }
+        }
+
+        if termination == Termination::LowerBound
+            && objective.fitness(&search.best_solution) > lower_bound
+        {
+            termination = Termination::TargetFitness;
+        }
+
+        self.config.rules = config_rules;
+        self.report(search, termination, model)
+    }
+
+    /// Resets the counters of the search and gets the solutions to start
+    /// from, which are the warm start or the solutions of the checkpoint.
+    fn start(&mut self, rules: &[Rule], model: &Model) -> Search {
+        let mut search = Search {
+            start_time: Instant::now(),
+            checkpoint_time: Instant::now(),
+            solution: Solution::new(),
+            state: model.initial_state(),
+            best_solution: Solution::new(),
+            best_decisions: vec![],
+            min_delay: TimeDiff::MAX,
+            no_improvements: 0,
+            iterations: 0,
+            restarts: 0,
+            repairs: 0,
+            deadlocks: 0,
+            stalls: 0,
+            restarted_by: Option::None,
+            max_stall: self
+                .config
+                .max_stall
+                .unwrap_or_else(|| default_max_stall(model)),
+            adapting: self.config.adapt_trains,
+            phase_restarts: 0,
+            phase_illegal: 0,
+            phase_improved: false,
+            best_by_trains: vec![TimeDiff::MAX; model.trains.len() + 1],
+            phase_best: Solution::new(),
+        };
+
+        self.checked_moves = 0;
+        self.decisions = vec![];
+        self.rule_statistics = self
+            .config
+            .rule_statistics
+            .then(|| RuleStatistics::new(rules.len()));
+        self.fitness = vec![];
+        self.improvements = vec![];
+        self.restart_statistics = STRATEGIES
+            .iter()
+            .map(|&strategy| (strategy, Statistics::default()))
+            .collect();
+        self.trains = model.used_trains;
+
+        // continue with the given solution
+        if let Some(warm_start) = self.warm_start.take() {
+            search.best_solution = warm_start.clone();
+            search.solution = warm_start;
+            search.min_delay = self.config.objective.fitness(&search.solution);
+        }
+
+        // the adaptation of the used trains of the checkpoint
+        let mut resumed_adaptation: Option<Adaptation> = Option::None;
+
+        // continue with the solutions and counters of the checkpoint
+        if let Some(checkpoint) = self.resume.take() {
+            search.best_solution = Solution::replay(model, &checkpoint.best);
+            search.solution = Solution::replay(model, &checkpoint.current);
+            search.min_delay = checkpoint.min_delay;
+            search.no_improvements = checkpoint.no_improvements;
+            search.iterations = checkpoint.iterations;
+            search.restarts = checkpoint.restarts;
+            search.start_time = search
+                .start_time
+                .checked_sub(Duration::from_millis(checkpoint.elapsed as u64))
+                .unwrap_or(search.start_time);
+            self.fitness = checkpoint.fitness.clone();
+            self.checked_moves = checkpoint.checked_moves;
+            self.trains = checkpoint.trains;
+            resumed_adaptation = Some(checkpoint.adaptation);
+        }
+
+        search.phase_best = search.best_solution.clone();
+
+        // continue the adaptation of the checkpoint, an adaptation that has
+        // finished is not started again
+        if let Some(adaptation) = resumed_adaptation {
+            search.adapting = search.adapting && adaptation.adapting;
+            search.phase_restarts = adaptation.phase_restarts;
+            search.phase_illegal = adaptation.phase_illegal;
+            search.phase_improved = adaptation.phase_improved;
+
+            if adaptation.best_by_trains.len() == search.best_by_trains.len() {
+                search.best_by_trains = adaptation.best_by_trains;
+            }
+
+            if search.adapting {
+                search.phase_best = Solution::replay(model, &adaptation.phase_best);
+            }
+        }
 
-                if no_improvements > self.max_iterations {
-                    break;
+        if let Some(last) = search.solution.0.last() {
+            search.state.clone_from(last);
+            search.state.next(model);
+        }
+
+        search
+    }
+
+    /// Moves the trains from the current state until `t_max`, all passengers
+    /// have arrived, the state is illegal or the search has to stop.
+    fn run(&mut self, search: &mut Search, rules: &[Rule], model: &Model) {
+        let mut run = Run {
+            progress_t: search.state.t,
+            ..Run::default()
+        };
+
+        while search.state.t <= model.t_max && self.tick(search, &mut run, rules, model) {}
+    }
+
+    /// Moves the trains at the current point in time and advances the state,
+    /// overloaded stations are repaired. Whether the run continues is
+    /// returned.
+    fn tick(&mut self, search: &mut Search, run: &mut Run, rules: &[Rule], model: &Model) -> bool {
+        let objective = self.config.objective;
+
+        if self.config.detect_deadlocks {
+            let detected = search.state.detect_deadlocks(model).to_vec();
+
+            // trains that block each other for more than one point in time
+            // are counted once as deadlock
+            for group in &detected {
+                if run.blocked.contains(group) && !run.deadlocks.contains(group) {
+                    run.deadlocks.push(group.clone());
+                    search.deadlocks += 1;
                 }
+            }
+
+            run.deadlocks.retain(|group| detected.contains(group));
+            run.blocked = detected;
+        }
+
+        self.find_neighbour(&mut search.state, rules, model);
+        search.solution.0.push(search.state.clone());
+        search.iterations += 1;
+
+        if search.state.has_progress() {
+            run.progress_t = search.state.t;
+        }
+
+        if objective.fitness(&search.solution) < search.min_delay {
+            search.min_delay = objective.fitness(&search.solution);
+            search.no_improvements = 0;
+        } else {
+            search.no_improvements += 1;
+        }
+
+        if self.config.track_fitness {
+            self.fitness.push(search.min_delay);
+        }
+
+        if search.no_improvements > self.config.max_iterations
+            || is_interrupted()
+            || self.exceeds_checked_moves()
+            || self.config.max_millis < search.start_time.elapsed().as_millis()
+        {
+            return false;
+        }
 
-                state.next(model);
+        search.state.next(model);
 
-                if !state.is_legal() || state.p_arrived.len() == model.passengers.len() {
-                    break;
+        // the train that caused an overloaded station waits instead of
+        // departing, the search continues with the repaired solution
+        while !search.state.is_legal() && run.repairs < self.config.max_repairs {
+            match repair(&search.solution, &search.state, model) {
+                Some(repaired) => {
+                    self.tabu.insert(repaired.tabu);
+                    search.solution = repaired.solution;
+                    self.decisions.retain(|d| d.is_in(&search.solution));
+                    search.state = repaired.state;
+                    run.repairs += 1;
+                    search.repairs += 1;
                 }
+                Option::None => break,
             }
+        }
+
+        if !search.state.is_legal() || search.state.p_arrived.len() == model.passengers.len() {
+            return false;
+        }
+
+        // the trains are stuck in a loop without moving passengers
+        if !run.stalled && search.state.t.saturating_sub(run.progress_t) > search.max_stall {
+            run.stalled = true;
+            search.stalls += 1;
+        }
+
+        !(run.stalled && self.config.max_stall.is_some())
+    }
 
-            if solution.fitness() < best_solution.fitness() {
-                best_solution = solution.clone();
+    /// Keeps the solution of the finished run when it improves the best
+    /// solution, otherwise the search continues with the best solution.
+    fn keep_best(&mut self, search: &mut Search) {
+        let objective = self.config.objective;
+
+        if !search.state.is_legal() {
+            search.phase_illegal += 1;
+        }
+
+        search.best_by_trains[self.trains] =
+            search.best_by_trains[self.trains].min(objective.fitness(&search.solution));
+
+        // the next iteration is based on the best solution of the current
+        // number of used trains, as other solutions use the removed trains
+        if search.adapting {
+            if objective.fitness(&search.solution) < objective.fitness(&search.phase_best) {
+                search.phase_best = search.solution.clone();
+                search.phase_improved = true;
             } else {
-                solution = best_solution.clone();
+                search.solution = search.phase_best.clone();
+                self.decisions.retain(|d| d.is_in(&search.solution));
             }
+        }
 
-            if no_improvements > self.max_iterations {
-                break;
+        // solutions with the same delay but less used trains are preferred
+        // when the number of used trains is adapted
+        let improved =
+            objective.fitness(&search.solution) < objective.fitness(&search.best_solution);
+
+        if improved
+            || search.adapting
+                && objective.fitness(&search.solution) == objective.fitness(&search.best_solution)
+                && search.solution.used_trains() < search.best_solution.used_trains()
+        {
+            if improved {
+                if let Some(strategy) = search.restarted_by {
+                    self.statistics(strategy).improvements += 1;
+                }
             }
 
-            start = rnd.gen_range(0..solution.0.len());
+            search.best_solution = search.solution.clone();
+            search.best_decisions.clone_from(&self.decisions);
 
-            if start == 0 {
-                state = model.initial_state();
-            } else {
-                state.clone_from(&solution.0[start - 1]);
-                state.next(model);
+            let progress = Progress {
+                iteration: search.iterations,
+                fitness: objective.fitness(&search.best_solution),
+                arrived: search.best_solution.arrived_passengers().len(),
+                elapsed: search.start_time.elapsed().as_millis(),
+            };
+
+            if let Some(observer) = &mut self.observer {
+                observer(&progress);
             }
 
-            solution.0.drain(start..);
+            self.improvements.push(progress);
+        } else if !search.adapting {
+            search.solution = search.best_solution.clone();
+            self.decisions.clone_from(&search.best_decisions);
+        }
+    }
 
-            if self.max_millis < start_time.elapsed().as_millis() {
-                break;
-            }
+    /// Ends the phase of the current number of used trains when it stagnates,
+    /// the next phase uses one train less until the remaining trains do not
+    /// reach the best delay anymore. Whether the slowest train has been
+    /// removed is returned.
+    fn adapt_trains(&mut self, search: &mut Search, model: &Model) -> bool {
+        let objective = self.config.objective;
+
+        if !search.adapting {
+            return false;
+        }
+
+        search.phase_restarts += 1;
+
+        if search.no_improvements <= self.config.max_iterations
+            && (search.phase_restarts < ADAPT_RESTARTS
+                || search.phase_improved && search.phase_illegal * 2 <= search.phase_restarts)
+        {
+            return false;
+        }
+
+        let mut reduced = false;
+
+        if search.best_by_trains[self.trains] > objective.fitness(&search.best_solution) {
+            // the remaining trains do not reach the best delay
+            self.trains = (self.trains + 1).min(model.used_trains);
+            search.adapting = false;
+        } else if self.trains == 1 {
+            search.adapting = false;
+        } else {
+            // restart without the slowest train
+            self.trains -= 1;
+            search.phase_best = Solution::new();
+            search.min_delay = TimeDiff::MAX;
+            search.no_improvements = 0;
+            reduced = true;
+        }
+
+        // the search continues with the best solution of all phases instead
+        // of the best solution of the last phase
+        if !search.adapting {
+            search.solution = search.best_solution.clone();
+            self.decisions.clone_from(&search.best_decisions);
+            search.min_delay = search
+                .min_delay
+                .min(objective.fitness(&search.best_solution));
+        }
+
+        search.phase_restarts = 0;
+        search.phase_illegal = 0;
+        search.phase_improved = false;
+
+        reduced
+    }
+
+    /// Chooses the point in time the next run starts from, a search without
+    /// the slowest train starts from the beginning.
+    fn restart_point(&mut self, search: &mut Search, reduced: bool, model: &Model) -> Time {
+        if reduced {
+            search.restarted_by = Option::None;
+            return 0;
         }
 
-        (best_solution, start_time.elapsed().as_millis())
+        let (strategy, t) =
+            self.config
+                .restart
+                .restart_point(&search.solution, model, &mut self.rnd);
+
+        search.restarted_by = Some(strategy);
+        t
+    }
+
+    /// Restarts the search from the given point in time of the solution.
+    fn restart(&mut self, search: &mut Search, start: Time, model: &Model) {
+        if let Some(strategy) = search.restarted_by {
+            self.statistics(strategy).restarts += 1;
+        }
+
+        search.restarts += 1;
+
+        if start == 0 {
+            search.state = model.initial_state();
+        } else {
+            search.state.clone_from(&search.solution.0[start - 1]);
+            search.state.next(model);
+        }
+
+        search.solution.0.drain(start..);
+        self.decisions.retain(|d| d.t < start);
+    }
+
+    /// Gets the reason to stop the search, if any. The search is stopped
+    /// gracefully and keeps the best solution when the process has been
+    /// interrupted.
+    fn stop(&self, search: &Search) -> Option<Termination> {
+        let elapsed = search.start_time.elapsed().as_millis();
+
+        if is_interrupted() {
+            Some(Termination::Interrupted)
+        } else if self.config.max_millis < elapsed {
+            Some(Termination::Time)
+        } else if self.exceeds_checked_moves() {
+            Some(Termination::CheckedMoves)
+        } else if self
+            .config
+            .max_restarts
+            .map_or(false, |max| search.restarts >= max)
+        {
+            Some(Termination::Restarts)
+        } else if self.is_stagnating(
+            self.config.objective.fitness(&search.best_solution),
+            elapsed,
+        ) {
+            Some(Termination::Stagnation)
+        } else {
+            Option::None
+        }
+    }
+
+    /// Writes a checkpoint when the search stops or the interval has passed.
+    fn write_checkpoint(&self, search: &mut Search, stop: bool, model: &Model) {
+        let (path, interval) = match &self.checkpoint {
+            Some((path, interval)) => (path, *interval),
+            Option::None => return,
+        };
+
+        if !stop && search.checkpoint_time.elapsed().as_millis() < interval {
+            return;
+        }
+
+        let checkpoint = Checkpoint {
+            model_hash: Checkpoint::model_hash(model),
+            t_max: model.t_max,
+            seed: self.rnd.get_seed(),
+            stream: self.rnd.get_stream(),
+            word_pos: self.rnd.get_word_pos(),
+            tabu: self.tabu.hashes(),
+            fitness: self.fitness.clone(),
+            checked_moves: self.checked_moves,
+            iterations: search.iterations,
+            restarts: search.restarts,
+            trains: self.trains,
+            no_improvements: search.no_improvements,
+            min_delay: search.min_delay,
+            elapsed: search.start_time.elapsed().as_millis(),
+            best: search.best_solution.moves(),
+            current: search.solution.moves(),
+            adaptation: Adaptation {
+                adapting: search.adapting,
+                phase_restarts: search.phase_restarts,
+                phase_illegal: search.phase_illegal,
+                phase_improved: search.phase_improved,
+                best_by_trains: search.best_by_trains.clone(),
+                phase_best: search.phase_best.moves(),
+            },
+        };
+
+        // a failed checkpoint must not stop a long running search, the next
+        // checkpoint is written after the interval
+        if let Err(error) = checkpoint.write(path) {
+            eprintln!("Failed to write checkpoint \"{}\": {}", path, error);
+        }
+
+        search.checkpoint_time = Instant::now();
+    }
+
+    /// Reports the best solution of the search, which is cleaned up when
+    /// configured.
+    fn report(&mut self, search: Search, termination: Termination, model: &Model) -> SearchReport {
+        let mut best_solution = search.best_solution;
+        let mut best_decisions = search.best_decisions;
+        let mut removed_moves = 0;
+
+        if self.config.clean_up && best_solution.is_legal() {
+            let (cleaned, removed) = clean_up(model, &best_solution, self.config.objective);
+
+            best_solution = cleaned;
+            removed_moves = removed;
+            best_decisions.retain(|d| d.is_in(&best_solution));
+        }
+
+        SearchReport {
+            solution: best_solution,
+            duration: search.start_time.elapsed().as_millis(),
+            checked_moves: self.checked_moves,
+            iterations: search.iterations,
+            restarts: search.restarts,
+            restart_statistics: self.restart_statistics.clone(),
+            improvements: self.improvements.clone(),
+            fitness: self.fitness.clone(),
+            repairs: search.repairs,
+            deadlocks: search.deadlocks,
+            stalls: search.stalls,
+            removed_moves,
+            decisions: best_decisions,
+            rule_statistics: self.rule_statistics.take(),
+            tabu_false_positive_rate: self.tabu.false_positive_rate(),
+            termination,
+        }
     }
 }
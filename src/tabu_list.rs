@@ -0,0 +1,185 @@
+use fxhash::FxBuildHasher;
+use linked_hash_set::LinkedHashSet;
+
+/// The approximate number of bytes an entry of the exact tabu list occupies,
+/// which includes the linked list node and the hash table bucket.
+pub const EXACT_ENTRY_BYTES: usize = 48;
+
+/// The number of generations of the rotating Bloom filter.
+const GENERATIONS: usize = 4;
+
+/// The number of bits that are set for each hash in the Bloom filter.
+const BLOOM_HASHES: u64 = 7;
+
+/// A list of hashes of visited states, the oldest hashes are evicted when the
+/// list is full.
+pub enum TabuList {
+    /// Stores the hashes in insertion order, there are no false positives
+    /// except for hash collisions.
+    Exact {
+        set: LinkedHashSet<u64, FxBuildHasher>,
+        capacity: usize,
+    },
+    /// Stores the hashes in a set of Bloom filters that are cleared one after
+    /// another, which needs far less memory but reports false positives.
+    Bloom {
+        generations: Vec<Vec<u64>>,
+        /// The number of set bits of each generation.
+        ones: Vec<usize>,
+        /// The index of the generation hashes are inserted into.
+        current: usize,
+        /// The number of hashes inserted into the current generation.
+        inserted: usize,
+        /// The number of hashes of a generation before it is rotated.
+        capacity: usize,
+    },
+}
+
+impl TabuList {
+    /// Constructs an exact tabu list for the given number of hashes.
+    pub fn exact(capacity: usize) -> TabuList {
+        TabuList::Exact {
+            set: LinkedHashSet::default(),
+            capacity,
+        }
+    }
+
+    /// Constructs a rotating Bloom filter that occupies the given number of
+    /// bytes.
+    ///
+    /// The capacity of each generation is chosen such that the false positive
+    /// rate of a full generation is about 1%.
+    pub fn bloom(memory: usize) -> TabuList {
+        let words = (memory / 8 / GENERATIONS).max(1);
+        let bits = words * 64;
+
+        TabuList::Bloom {
+            generations: (0..GENERATIONS).map(|_| vec![0; words]).collect(),
+            ones: vec![0; GENERATIONS],
+            current: 0,
+            inserted: 0,
+            capacity: ((bits as f64 * std::f64::consts::LN_2 / BLOOM_HASHES as f64) as usize)
+                .max(1),
+        }
+    }
+
+    /// Determines whether the given hash is in the tabu list.
+    pub fn contains(&self, hash: u64) -> bool {
+        match self {
+            TabuList::Exact { set, .. } => set.contains(&hash),
+            TabuList::Bloom { generations, .. } => generations.iter().any(|generation| {
+                bloom_bits(hash, generation.len() * 64)
+                    .all(|bit| generation[bit / 64] & (1 << (bit % 64)) != 0)
+            }),
+        }
+    }
+
+    /// Inserts the given hash and evicts the oldest hashes when the list is
+    /// full.
+    pub fn insert(&mut self, hash: u64) {
+        match self {
+            TabuList::Exact { set, capacity } => {
+                set.insert(hash);
+
+                if set.len() > *capacity {
+                    set.pop_front();
+                }
+            }
+            TabuList::Bloom {
+                generations,
+                ones,
+                current,
+                inserted,
+                capacity,
+            } => {
+                // clear the oldest generation when the current one is full
+                if *inserted >= *capacity {
+                    *current = (*current + 1) % generations.len();
+                    *inserted = 0;
+                    ones[*current] = 0;
+                    generations[*current].iter_mut().for_each(|word| *word = 0);
+                }
+
+                let generation = &mut generations[*current];
+
+                for bit in bloom_bits(hash, generation.len() * 64) {
+                    let mask = 1 << (bit % 64);
+
+                    if generation[bit / 64] & mask == 0 {
+                        generation[bit / 64] |= mask;
+                        ones[*current] += 1;
+                    }
+                }
+
+                *inserted += 1;
+            }
+        }
+    }
+
+    /// Gets the hashes of an exact tabu list in insertion order, the hashes of
+    /// a Bloom filter cannot be restored and an empty vector is returned.
+    pub fn hashes(&self) -> Vec<u64> {
+        match self {
+            TabuList::Exact { set, .. } => set.iter().copied().collect(),
+            TabuList::Bloom { .. } => vec![],
+        }
+    }
+
+    /// Gets the estimated probability that a hash which has not been inserted
+    /// is reported to be in the tabu list.
+    pub fn false_positive_rate(&self) -> f64 {
+        match self {
+            // probability of a collision with any of the 64 bit hashes
+            TabuList::Exact { set, .. } => set.len() as f64 / 2f64.powi(64),
+            TabuList::Bloom {
+                generations, ones, ..
+            } => {
+                let negative: f64 = generations
+                    .iter()
+                    .zip(ones.iter())
+                    .map(|(generation, &ones)| {
+                        1.0 - (ones as f64 / (generation.len() * 64) as f64)
+                            .powi(BLOOM_HASHES as i32)
+                    })
+                    .product();
+
+                1.0 - negative
+            }
+        }
+    }
+}
+
+/// Gets the bits of the given hash in a Bloom filter of the given size by
+/// double hashing.
+fn bloom_bits(hash: u64, bits: usize) -> impl Iterator<Item = usize> {
+    let step = hash.rotate_left(32) | 1;
+
+    (0..BLOOM_HASHES).map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bits as u64) as usize)
+}
+
+#[test]
+fn it_evicts_the_oldest_hashes() {
+    let mut exact = TabuList::exact(2);
+
+    (1..=3).for_each(|hash| exact.insert(hash));
+
+    assert!(!exact.contains(1));
+    assert!(exact.contains(2) && exact.contains(3));
+    assert_eq!(exact.hashes(), vec![2, 3]);
+
+    let mut bloom = TabuList::bloom(4096);
+    let hashes: Vec<u64> = (0..20000u64).map(|i| fxhash::hash64(&i)).collect();
+
+    hashes.iter().for_each(|&hash| bloom.insert(hash));
+
+    // the latest hashes are kept, the oldest generations have been cleared
+    assert!(hashes[19900..].iter().all(|&hash| bloom.contains(hash)));
+    assert!(
+        hashes[..100]
+            .iter()
+            .filter(|&&hash| bloom.contains(hash))
+            .count()
+            < 50
+    );
+    assert!(bloom.false_positive_rate() < 0.1);
+}
@@ -8,7 +8,7 @@ pub type Id = types::Id;
 pub type Speed = Decimal;
 pub type Capacity = types::Capacity;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Hash)]
 pub enum StartStation {
     Any,
     Station(SId),
@@ -55,7 +55,7 @@ impl fmt::Display for Location {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 pub struct Train {
     pub name: String,
     pub start: StartStation,
@@ -0,0 +1,318 @@
+use crate::model::Model;
+use crate::rules::{get_rules_by_names, requires_deadlocks, DEFAULT_RULE_SETS, RULE_SETS};
+use crate::score::{default_weights, Ranking, Scoring};
+use crate::search::{SearchConfig, Termination};
+use crate::tabu::TabuGeneticSearch;
+use fxhash::FxHashSet;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cmp::Ordering;
+
+/// The configuration of the rule tuning.
+pub struct TuningConfig {
+    /// The number of seeded searches of each configuration on each instance.
+    pub runs: usize,
+
+    /// The maximum number of milli seconds of each search.
+    pub max_millis: u128,
+
+    /// The maximum number of compared moves of each search, which makes the
+    /// tuning independent of the machine.
+    pub max_checked_moves: Option<usize>,
+
+    /// The number of rule configurations that are evaluated.
+    pub candidates: usize,
+
+    /// The seed of the random generator that changes the configurations.
+    pub seed: u64,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        TuningConfig {
+            runs: 3,
+            max_millis: 1000,
+            max_checked_moves: None,
+            candidates: 20,
+            seed: 0,
+        }
+    }
+}
+
+/// The results of the searches with a rule configuration.
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    /// The rule sets in the order they are applied.
+    pub rule_sets: Vec<&'static str>,
+
+    /// The weights of the score rules, the moves are ranked by the weighted
+    /// sum of their scores instead of the rule sets when weights are given.
+    pub weights: Option<Vec<f64>>,
+
+    /// The fraction of searches that have reached the lower bound.
+    pub success_rate: f64,
+
+    /// The mean delay of all searches.
+    pub mean_delay: f64,
+
+    /// The mean number of milli seconds until the successful searches have
+    /// found their best solution, `None` if no search has been successful.
+    pub mean_time_to_optimum: Option<f64>,
+}
+
+impl Evaluation {
+    /// Compares the evaluations, better evaluations are less: by the success
+    /// rate, the mean delay and the time to the optimum.
+    pub fn compare(&self, other: &Evaluation) -> Ordering {
+        other
+            .success_rate
+            .total_cmp(&self.success_rate)
+            .then(self.mean_delay.total_cmp(&other.mean_delay))
+            .then(
+                self.mean_time_to_optimum
+                    .unwrap_or(f64::MAX)
+                    .total_cmp(&other.mean_time_to_optimum.unwrap_or(f64::MAX)),
+            )
+    }
+
+    /// Gets the rule configuration as file that can be read by
+    /// [parse_rule_sets](crate::rules::parse_rule_sets) and
+    /// [parse_weights](crate::score::parse_weights), the results are written
+    /// as comments.
+    pub fn to_rules_file(&self) -> String {
+        let mut string = format!(
+            "# success rate {:.2}, mean delay {:.2}, mean time to optimum {}\n",
+            self.success_rate,
+            self.mean_delay,
+            match self.mean_time_to_optimum {
+                Some(time) => format!("{:.0}ms", time),
+                None => "-".to_owned(),
+            }
+        );
+
+        for name in &self.rule_sets {
+            string.push_str(name);
+            string.push('\n');
+        }
+
+        if let Some(weights) = &self.weights {
+            string.push_str(&format!(
+                "weights {}\n",
+                weights
+                    .iter()
+                    .map(|weight| weight.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ));
+        }
+
+        string
+    }
+}
+
+/// Evaluates the rule sets, or the weighted ranking when weights are given,
+/// by seeded searches on each model.
+pub fn evaluate(
+    rule_sets: &[&'static str],
+    weights: Option<&[f64]>,
+    models: &[Model],
+    config: &TuningConfig,
+) -> Evaluation {
+    let mut searches = 0;
+    let mut successes = 0;
+    let mut delay = 0.0;
+    let mut time_to_optimum = 0.0;
+
+    for model in models {
+        for seed in 0..config.runs {
+            let mut search_config = SearchConfig::default()
+                .time(config.max_millis)
+                .seed(seed as u64)
+                .rules(get_rules_by_names(rule_sets).expect("Unknown rule set"))
+                .detect_deadlocks(requires_deadlocks(rule_sets));
+
+            if let Some(max_checked_moves) = config.max_checked_moves {
+                search_config = search_config.max_checked_moves(max_checked_moves);
+            }
+
+            if let Some(weights) = weights {
+                search_config =
+                    search_config.scoring(Scoring::new(Ranking::Weighted(weights.to_vec())));
+            }
+
+            let report = TabuGeneticSearch::new(search_config).search(model);
+
+            searches += 1;
+            delay += report.solution.fitness() as f64;
+
+            if report.termination == Termination::LowerBound {
+                successes += 1;
+                time_to_optimum += report
+                    .improvements
+                    .last()
+                    .map_or(0, |progress| progress.elapsed)
+                    as f64;
+            }
+        }
+    }
+
+    Evaluation {
+        rule_sets: rule_sets.to_vec(),
+        weights: weights.map(|weights| weights.to_vec()),
+        success_rate: successes as f64 / searches.max(1) as f64,
+        mean_delay: delay / searches.max(1) as f64,
+        mean_time_to_optimum: match successes {
+            0 => None,
+            _ => Some(time_to_optimum / successes as f64),
+        },
+    }
+}
+
+/// Changes the weights randomly: a weight is doubled or halved. Without
+/// weights, the search switches to the weighted ranking with the default
+/// weights, which keep the priority of the score rules.
+fn neighbour_weights(weights: Option<&[f64]>, rnd: &mut ChaCha8Rng) -> Vec<f64> {
+    match weights {
+        Some(weights) => {
+            let mut weights = weights.to_vec();
+            let i = rnd.gen_range(0..weights.len());
+
+            weights[i] *= if rnd.gen_bool(0.5) { 2.0 } else { 0.5 };
+            weights
+        }
+        None => default_weights(),
+    }
+}
+
+/// Changes the rule sets randomly: a rule set is removed, an unused rule set
+/// is inserted or two rule sets are swapped.
+fn neighbour(rule_sets: &[&'static str], rnd: &mut ChaCha8Rng) -> Vec<&'static str> {
+    let mut rule_sets = rule_sets.to_vec();
+    let unused: Vec<&'static str> = RULE_SETS
+        .iter()
+        .filter(|name| !rule_sets.contains(name))
+        .copied()
+        .collect();
+
+    match rnd.gen_range(0..3) {
+        0 if rule_sets.len() > 1 => {
+            rule_sets.remove(rnd.gen_range(0..rule_sets.len()));
+        }
+        1 if !unused.is_empty() => {
+            let i = rnd.gen_range(0..=rule_sets.len());
+
+            rule_sets.insert(i, unused.choose(rnd).unwrap());
+        }
+        _ if rule_sets.len() > 1 => {
+            let i = rnd.gen_range(0..rule_sets.len());
+            let j = rnd.gen_range(0..rule_sets.len());
+
+            rule_sets.swap(i, j);
+        }
+        _ => (),
+    }
+
+    rule_sets
+}
+
+/// Tunes the subset and the order of the rule sets and the weights of the
+/// score rules on the given models by a hill climbing that starts with the
+/// default rule sets. A change either changes the weights or the rule sets
+/// and switches back to the rules, as the rule sets do not decide the moves
+/// while the weighted ranking is used. All evaluated configurations are
+/// returned, the best configuration comes first.
+///
+/// The closure is called with each evaluation, e.g. to report the progress.
+pub fn tune(
+    models: &[Model],
+    config: &TuningConfig,
+    mut observer: impl FnMut(&Evaluation),
+) -> Vec<Evaluation> {
+    let mut rnd = ChaCha8Rng::seed_from_u64(config.seed);
+    let mut visited: FxHashSet<(Vec<&'static str>, Vec<u64>)> = FxHashSet::default();
+    let key = |rule_sets: &[&'static str], weights: Option<&[f64]>| {
+        (
+            rule_sets.to_vec(),
+            weights.map_or(vec![], |weights| {
+                weights.iter().map(|weight| weight.to_bits()).collect()
+            }),
+        )
+    };
+    let mut current = evaluate(&DEFAULT_RULE_SETS, None, models, config);
+    let mut evaluations = vec![current.clone()];
+
+    visited.insert(key(&current.rule_sets, None));
+    observer(&current);
+
+    // the number of changes without a new configuration
+    let mut attempts = 0;
+
+    while evaluations.len() < config.candidates && attempts < config.candidates * 10 {
+        // the ranking is switched in a quarter of the changes
+        let (rule_sets, weights) = match (current.weights.as_deref(), rnd.gen_range(0..4)) {
+            (None, 0) => (
+                current.rule_sets.clone(),
+                Some(neighbour_weights(None, &mut rnd)),
+            ),
+            (None, _) | (Some(_), 0) => (neighbour(&current.rule_sets, &mut rnd), None),
+            (weights, _) => (
+                current.rule_sets.clone(),
+                Some(neighbour_weights(weights, &mut rnd)),
+            ),
+        };
+
+        if !visited.insert(key(&rule_sets, weights.as_deref())) {
+            attempts += 1;
+            continue;
+        }
+
+        let evaluation = evaluate(&rule_sets, weights.as_deref(), models, config);
+
+        observer(&evaluation);
+
+        if evaluation.compare(&current).is_le() {
+            current = evaluation.clone();
+        }
+
+        evaluations.push(evaluation);
+    }
+
+    evaluations.sort_by(|a, b| a.compare(b));
+    evaluations
+}
+
+#[test]
+fn it_ranks_evaluations_by_success_rate_first() {
+    let evaluation = |success_rate, mean_delay| Evaluation {
+        rule_sets: DEFAULT_RULE_SETS.to_vec(),
+        weights: None,
+        success_rate,
+        mean_delay,
+        mean_time_to_optimum: None,
+    };
+
+    assert!(evaluation(1.0, 5.0).compare(&evaluation(0.5, 0.0)).is_lt());
+    assert!(evaluation(0.5, 1.0).compare(&evaluation(0.5, 2.0)).is_lt());
+
+    // the rules file can be read by the solver
+    let rules = crate::rules::parse_rule_sets(&evaluation(1.0, 0.0).to_rules_file()).unwrap();
+
+    assert_eq!(rules.len(), crate::rules::get_rules().len());
+
+    // the tuned weights are read as well
+    let weighted = Evaluation {
+        weights: Some(default_weights()),
+        ..evaluation(1.0, 0.0)
+    };
+    let rules_file = weighted.to_rules_file();
+
+    assert_eq!(
+        crate::rules::parse_rule_sets(&rules_file).unwrap().len(),
+        crate::rules::get_rules().len()
+    );
+    assert_eq!(
+        crate::score::parse_weights(&rules_file).unwrap(),
+        Some(default_weights())
+    );
+}